@@ -1,43 +1,182 @@
 use {
     proc_macro::TokenStream,
+    proc_macro_crate::{FoundCrate, crate_name},
+    proc_macro2::{Span, TokenStream as TokenStream2},
     quote::quote,
-    syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type, parse_macro_input},
+    syn::{
+        Attribute, Data, DataEnum, DeriveInput, Fields, GenericArgument, Ident, PathArguments,
+        Type, parse_macro_input,
+    },
 };
 
 #[proc_macro_derive(ConfigMenu, attributes(config_menu))]
 pub fn derive_config_menu(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
+    let krate = crate_path();
+
+    if let Data::Enum(data) = &input.data {
+        return derive_enum(&krate, name, &input.attrs, data);
+    }
+
+    let serde_attrs = match parse_serde_attrs(&input.attrs) {
+        Ok(serde_attrs) => serde_attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let field_metadata = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => {
-                let field_info = fields.named.iter().map(|f| {
+                let mut field_info: Vec<_> = fields.named.iter().filter_map(|f| {
+                    let attrs = match parse_field_attrs(&f.attrs) {
+                        Ok(attrs) => attrs,
+                        Err(e) => return Some((None, e.to_compile_error())),
+                    };
+                    if attrs.skip {
+                        return None;
+                    }
+                    let render_markup = attrs.render_markup;
+                    let readonly = attrs.readonly;
+                    let secret = attrs.secret;
+                    let multiline = attrs.multiline;
                     let field_name = &f.ident;
                     let field_type = &f.ty;
                     let field_name_str = field_name.as_ref().unwrap().to_string();
+                    let display_name = attrs.rename.clone().unwrap_or_else(|| field_name_str.clone());
+                    // The key serde writes the field under, which is what
+                    // lookups into a serialized config have to use.
+                    let serialized_name = match parse_serde_attrs(&f.attrs) {
+                        Ok(field_serde_attrs) => field_serde_attrs.rename,
+                        Err(e) => return Some((None, e.to_compile_error())),
+                    }
+                    .unwrap_or_else(|| {
+                        let unraw = field_name_str.trim_start_matches("r#");
+                        serde_attrs
+                            .rename_all
+                            .as_deref()
+                            .and_then(|rule| rename_field(unraw, rule))
+                            .unwrap_or_else(|| unraw.to_string())
+                    });
+                    let description = match &attrs.help {
+                        Some(help) => quote! { Some(#help) },
+                        None => quote! { None },
+                    };
+                    let group = match &attrs.group {
+                        Some(group) => quote! { Some(#group) },
+                        None => quote! { None },
+                    };
+                    let min = match attrs.min {
+                        Some(min) => quote! { Some(#min) },
+                        None => quote! { None },
+                    };
+                    let max = match attrs.max {
+                        Some(max) => quote! { Some(#max) },
+                        None => quote! { None },
+                    };
+                    let step = match attrs.step {
+                        Some(step) => quote! { Some(#step) },
+                        None => quote! { None },
+                    };
+
+                    let (mut is_nested, is_option, is_vec, mut inner_type, inner_type_ident) = analyze_type(field_type);
+                    let tuple_elements: Vec<&Type> = match field_type {
+                        Type::Tuple(tuple) => tuple.elems.iter().collect(),
+                        _ => Vec::new(),
+                    };
+                    let is_tuple = !tuple_elements.is_empty();
+                    let is_array = matches!(field_type, Type::Array(_));
+                    let mut tuple_element_types = Vec::new();
+                    for element in &tuple_elements {
+                        let (nested, option, vec, element_type, _) = analyze_type(element);
+                        if nested || option || vec {
+                            let error = syn::Error::new_spanned(element, "tuple elements must be primitive types");
+                            return Some((None, error.to_compile_error()));
+                        }
+                        tuple_element_types.push(element_type);
+                    }
+                    let map_key_type = map_types(field_type).map(|(key, _)| key);
+                    let is_map = map_key_type.is_some();
+                    // Map entries are reached by position through the value iterators.
+                    let (element_ref, element_mut) = if is_map {
+                        (quote! { values().nth(index) }, quote! { values_mut().nth(index) })
+                    } else {
+                        (quote! { get(index) }, quote! { get_mut(index) })
+                    };
+                    // Types without a Default start new values from a seed text instead.
+                    let seed = default_seed(field_type);
+                    let new_value = if let Some(seed) = seed {
+                        quote! { #krate::ParsableField::parse_from_string(#seed.to_string())? }
+                    } else {
+                        quote! { new_value() }
+                    };
+                    let new_element = if let Some(seed) = seed {
+                        quote! {
+                            if let Ok(element) = #krate::ParsableField::parse_from_string(#seed.to_string()) {
+                                c.#field_name.push(element);
+                            }
+                        }
+                    } else {
+                        quote! { c.#field_name.push(new_value()); }
+                    };
+                    // Without a seed, closures that create elements are only
+                    // emitted when the element type implements Default.
+                    let element_default = element_type(field_type).map(|element| quote! {
+                        {
+                            use #krate::{ProbeDefault as _, ProbeNoDefault as _};
+                            (&&#krate::DefaultProbe::<#element>::new()).default_fn()
+                        }
+                    });
+                    let when_creatable = |closure: TokenStream2| match (seed, &element_default) {
+                        (None, Some(default_fn)) => quote! {
+                            match #default_fn {
+                                Some(new_value) => Some(Box::new(#closure)),
+                                None => None,
+                            }
+                        },
+                        _ => quote! { Some(Box::new(#closure)) },
+                    };
 
-                    let (is_nested, is_option, is_vec, inner_type, inner_type_ident) = analyze_type(field_type);
+                    if let Some(as_type) = attrs.as_type {
+                        is_nested = false;
+                        inner_type = as_type;
+                    }
 
-                    let (nested_getter, nested_metadata_getter, nested_setter) = if is_nested {
+                    let (nested_getter, nested_metadata_getter, nested_setter) = if is_nested && is_vec {
+                        let inner_type_tokens = &inner_type_ident;
+                        (
+                            quote! { None },
+                            quote! {
+                                Some(Box::new(|| {
+                                    <#inner_type_tokens as #krate::ConfigMenuTrait>::field_metadata()
+                                }))
+                            },
+                            quote! { None },
+                        )
+                    } else if is_nested {
                         let inner_type_tokens = &inner_type_ident;
+                        let (nested_ref, nested_value) = if is_option {
+                            (quote! { c.#field_name.as_ref() }, quote! { Some(nested.clone()) })
+                        } else {
+                            (quote! { Some(&c.#field_name) }, quote! { nested.clone() })
+                        };
                         (
                             quote! {
                                 Some(Box::new(|config: &dyn std::any::Any| -> Option<Box<dyn std::any::Any>> {
                                     config.downcast_ref::<#name>()
-                                        .map(|c| Box::new(c.#field_name.clone()) as Box<dyn std::any::Any>)
+                                        .and_then(|c| #nested_ref)
+                                        .map(|nested| Box::new(nested.clone()) as Box<dyn std::any::Any>)
                                 }))
                             },
                             quote! {
                                 Some(Box::new(|| {
-                                    <#inner_type_tokens as ::config_menu::ConfigMenuTrait>::get_field_metadata()
+                                    <#inner_type_tokens as #krate::ConfigMenuTrait>::field_metadata()
                                 }))
                             },
                             quote! {
                                 Some(Box::new(|config: &mut dyn std::any::Any, value: Box<dyn std::any::Any>| -> Result<(), String> {
                                     if let Some(c) = config.downcast_mut::<#name>() {
                                         if let Some(nested) = value.downcast_ref::<#inner_type_tokens>() {
-                                            c.#field_name = nested.clone();
+                                            c.#field_name = #nested_value;
                                             Ok(())
                                         } else {
                                             Err(format!("Type mismatch when setting nested field '{}'", #field_name_str))
@@ -52,20 +191,352 @@ pub fn derive_config_menu(input: TokenStream) -> TokenStream {
                         (quote! { None }, quote! { None }, quote! { None })
                     };
 
-                    quote! {
-                        ::config_menu::FieldMetadata {
+                    let swapper = quote! {
+                        Some(Box::new(|config: &mut dyn std::any::Any, a: usize, b: usize| {
+                            if let Some(c) = config.downcast_mut::<#name>() {
+                                let len = c.#field_name.len();
+                                if a >= len || b >= len {
+                                    return Err(format!("No element {} in field '{}'", a.max(b), #field_name_str));
+                                }
+                                c.#field_name.swap(a, b);
+                                Ok(())
+                            } else {
+                                Err("Type mismatch".to_string())
+                            }
+                        }))
+                    };
+                    let (element_pusher, element_remover, element_swapper) = if is_tuple {
+                        (quote! { None }, quote! { None }, quote! { None })
+                    } else if is_array {
+                        // Arrays keep their length, but elements can still be reordered.
+                        (quote! { None }, quote! { None }, swapper)
+                    } else if is_map {
+                        (
+                            quote! { None },
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any, index: usize| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        let key = c.#field_name.keys().nth(index).cloned().ok_or_else(|| {
+                                            format!("No element {} in field '{}'", index, #field_name_str)
+                                        })?;
+                                        c.#field_name.remove(&key);
+                                        Ok(())
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }))
+                            },
+                            quote! { None },
+                        )
+                    } else if is_vec {
+                        (
+                            when_creatable(quote! {
+                                move |config: &mut dyn std::any::Any| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        #new_element
+                                        Ok(())
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }
+                            }),
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any, index: usize| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        if index >= c.#field_name.len() {
+                                            return Err(format!("No element {} in field '{}'", index, #field_name_str));
+                                        }
+                                        c.#field_name.remove(index);
+                                        Ok(())
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }))
+                            },
+                            swapper,
+                        )
+                    } else {
+                        (quote! { None }, quote! { None }, quote! { None })
+                    };
+
+                    let (entry_keys, entry_inserter) = if let Some(key_type) = map_key_type {
+                        (
+                            quote! {
+                                Some(Box::new(|config: &dyn std::any::Any| {
+                                    config.downcast_ref::<#name>()
+                                        .map(|c| c.#field_name.keys().map(#krate::map_key_text).collect())
+                                }))
+                            },
+                            when_creatable(quote! {
+                                move |config: &mut dyn std::any::Any, key: String| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        let key = <#key_type as #krate::ParsableField>::parse_from_string(key)?;
+                                        if c.#field_name.contains_key(&key) {
+                                            return Err(format!(
+                                                "Key '{}' already exists in field '{}'",
+                                                #krate::map_key_text(&key),
+                                                #field_name_str
+                                            ));
+                                        }
+                                        c.#field_name.insert(key, #new_value);
+                                        Ok(())
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }
+                            }),
+                        )
+                    } else {
+                        (quote! { None }, quote! { None })
+                    };
+
+                    let (vec_len, element_getter, element_setter) = if is_tuple {
+                        let len = tuple_elements.len();
+                        let positions: Vec<usize> = (0..len).collect();
+                        let members: Vec<syn::Index> = (0..len).map(syn::Index::from).collect();
+                        (
+                            quote! {
+                                Some(Box::new(|_: &dyn std::any::Any| Some(#len)))
+                            },
+                            quote! {
+                                Some(Box::new(|config: &dyn std::any::Any, index: usize| {
+                                    let c = config.downcast_ref::<#name>()?;
+                                    match index {
+                                        #(#positions => Some(#krate::format_field_value(&c.#field_name.#members)),)*
+                                        _ => None,
+                                    }
+                                }))
+                            },
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any, index: usize, value: String| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        match index {
+                                            #(#positions => #krate::parse_and_set(&mut c.#field_name.#members, value),)*
+                                            _ => Err(format!("No element {} in field '{}'", index, #field_name_str)),
+                                        }
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }))
+                            },
+                        )
+                    } else if is_vec {
+                        (
+                            quote! {
+                                Some(Box::new(|config: &dyn std::any::Any| {
+                                    config.downcast_ref::<#name>().map(|c| c.#field_name.len())
+                                }))
+                            },
+                            quote! {
+                                Some(Box::new(|config: &dyn std::any::Any, index: usize| {
+                                    config.downcast_ref::<#name>()
+                                        .and_then(|c| c.#field_name.#element_ref)
+                                        .map(#krate::format_field_value)
+                                }))
+                            },
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any, index: usize, value: String| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        let element = c.#field_name.#element_mut.ok_or_else(|| {
+                                            format!("No element {} in field '{}'", index, #field_name_str)
+                                        })?;
+                                        #krate::parse_and_set(element, value)
+                                    } else {
+                                        Err("Type mismatch".to_string())
+                                    }
+                                }))
+                            },
+                        )
+                    } else {
+                        (quote! { None }, quote! { None }, quote! { None })
+                    };
+
+                    let (element_nested_getter, element_nested_setter) = if is_vec && is_nested {
+                        let inner_type_tokens = &inner_type_ident;
+                        (
+                            quote! {
+                                Some(Box::new(|config: &dyn std::any::Any, index: usize| -> Option<Box<dyn std::any::Any>> {
+                                    config.downcast_ref::<#name>()
+                                        .and_then(|c| c.#field_name.#element_ref)
+                                        .map(|element| Box::new(element.clone()) as Box<dyn std::any::Any>)
+                                }))
+                            },
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any, index: usize, value: Box<dyn std::any::Any>| -> Result<(), String> {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        let element = c.#field_name.#element_mut.ok_or_else(|| {
+                                            format!("No element {} in field '{}'", index, #field_name_str)
+                                        })?;
+                                        if let Some(nested) = value.downcast_ref::<#inner_type_tokens>() {
+                                            *element = nested.clone();
+                                            Ok(())
+                                        } else {
+                                            Err(format!("Type mismatch when setting element of '{}'", #field_name_str))
+                                        }
+                                    } else {
+                                        Err("Config type mismatch".to_string())
+                                    }
+                                }))
+                            },
+                        )
+                    } else {
+                        (quote! { None }, quote! { None })
+                    };
+
+                    let resetter = if let Some(default) = &attrs.default {
+                        quote! {
+                            Some(Box::new(|config: &mut dyn std::any::Any| {
+                                if let Some(c) = config.downcast_mut::<#name>() {
+                                    #krate::parse_and_set(&mut c.#field_name, #default.to_string())
+                                } else {
+                                    Err("Type mismatch".to_string())
+                                }
+                            }))
+                        }
+                    } else if is_array && seed.is_none() {
+                        when_creatable(quote! {
+                            move |config: &mut dyn std::any::Any| {
+                                if let Some(c) = config.downcast_mut::<#name>() {
+                                    c.#field_name = std::array::from_fn(|_| new_value());
+                                    Ok(())
+                                } else {
+                                    Err("Type mismatch".to_string())
+                                }
+                            }
+                        })
+                    } else if is_option
+                        || (is_vec && !is_tuple && !is_array)
+                        || (!is_nested && seed.is_none() && tuple_elements.iter().all(|element| default_seed(element).is_none()))
+                    {
+                        quote! {
+                            Some(Box::new(|config: &mut dyn std::any::Any| {
+                                if let Some(c) = config.downcast_mut::<#name>() {
+                                    c.#field_name = Default::default();
+                                    Ok(())
+                                } else {
+                                    Err("Type mismatch".to_string())
+                                }
+                            }))
+                        }
+                    } else {
+                        quote! { None }
+                    };
+
+                    let validator = match &attrs.validate {
+                        Some(validate) => quote! {
+                            Some(Box::new(|config: &dyn std::any::Any| {
+                                match config.downcast_ref::<#name>() {
+                                    Some(c) => #validate(&c.#field_name),
+                                    None => Err("Type mismatch".to_string()),
+                                }
+                            }))
+                        },
+                        None => quote! { None },
+                    };
+
+                    let option_default = if let Some(seed) = seed {
+                        quote! { #krate::ParsableField::parse_from_string(#seed.to_string()).ok() }
+                    } else {
+                        quote! { Some(new_value()) }
+                    };
+                    let (option_clearer, option_default_setter) = if is_option {
+                        (
+                            quote! {
+                                Some(Box::new(|config: &mut dyn std::any::Any| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        c.#field_name = None;
+                                    }
+                                }))
+                            },
+                            when_creatable(quote! {
+                                move |config: &mut dyn std::any::Any| {
+                                    if let Some(c) = config.downcast_mut::<#name>() {
+                                        c.#field_name = #option_default;
+                                    }
+                                }
+                            }),
+                        )
+                    } else {
+                        (quote! { None }, quote! { None })
+                    };
+
+                    let element_types = if is_tuple {
+                        quote! { Some(vec![#(#krate::FieldType::from_str(#tuple_element_types)),*]) }
+                    } else {
+                        quote! { None }
+                    };
+
+                    let (is_nested_tokens, field_type, variants) = if is_tuple {
+                        (
+                            quote! { false },
+                            quote! { #krate::FieldType::Tuple },
+                            quote! { None },
+                        )
+                    } else if is_nested {
+                        let inner_type_tokens = &inner_type_ident;
+                        (
+                            quote! {
+                                <#inner_type_tokens as #krate::ConfigMenuTrait>::variants().is_none()
+                            },
+                            quote! {
+                                if <#inner_type_tokens as #krate::ConfigMenuTrait>::variants().is_some() {
+                                    #krate::FieldType::Enum
+                                } else {
+                                    #krate::FieldType::Nested
+                                }
+                            },
+                            quote! {
+                                <#inner_type_tokens as #krate::ConfigMenuTrait>::variants()
+                            },
+                        )
+                    } else {
+                        (
+                            quote! { false },
+                            quote! { #krate::FieldType::from_str(#inner_type) },
+                            quote! { None },
+                        )
+                    };
+
+                    Some((attrs.order, quote! {
+                        #krate::FieldMetadata {
                             name: #field_name_str,
-                            is_nested: #is_nested,
+                            serialized_name: #serialized_name,
+                            display_name: #display_name,
+                            description: #description,
+                            group: #group,
+                            min: #min,
+                            max: #max,
+                            step: #step,
+                            field_id: #krate::stable_field_id(#field_name_str),
+                            is_nested: #is_nested_tokens,
                             is_option: #is_option,
                             is_vec: #is_vec,
-                            field_type: ::config_menu::FieldType::from_str(#inner_type),
+                            is_map: #is_map,
+                            is_array: #is_array,
+                            field_type: #field_type,
+                            variants: #variants,
+                            render_markup: #render_markup,
+                            readonly: #readonly,
+                            secret: #secret,
+                            multiline: #multiline,
                             getter: Box::new(|config: &dyn std::any::Any| {
                                 config.downcast_ref::<#name>()
-                                    .map(|c| ::config_menu::format_field_value(&c.#field_name))
+                                    .map(|c| #krate::format_field_value(&c.#field_name))
                             }),
                             setter: Box::new(|config: &mut dyn std::any::Any, value: String| {
                                 if let Some(c) = config.downcast_mut::<#name>() {
-                                    ::config_menu::parse_and_set(&mut c.#field_name, value)
+                                    #krate::parse_and_set(&mut c.#field_name, value)
+                                } else {
+                                    Err("Type mismatch".to_string())
+                                }
+                            }),
+                            value_getter: Box::new(|config: &dyn std::any::Any| {
+                                config.downcast_ref::<#name>()
+                                    .and_then(|c| #krate::field_to_value(&c.#field_name))
+                            }),
+                            value_setter: Box::new(|config: &mut dyn std::any::Any, value: #krate::serde_json::Value| {
+                                if let Some(c) = config.downcast_mut::<#name>() {
+                                    #krate::set_from_value(&mut c.#field_name, value)
                                 } else {
                                     Err("Type mismatch".to_string())
                                 }
@@ -73,9 +544,29 @@ pub fn derive_config_menu(input: TokenStream) -> TokenStream {
                             nested_getter: #nested_getter,
                             nested_metadata_getter: #nested_metadata_getter,
                             nested_setter: #nested_setter,
+                            vec_len: #vec_len,
+                            element_getter: #element_getter,
+                            element_setter: #element_setter,
+                            element_nested_getter: #element_nested_getter,
+                            element_nested_setter: #element_nested_setter,
+                            element_pusher: #element_pusher,
+                            element_remover: #element_remover,
+                            element_swapper: #element_swapper,
+                            entry_keys: #entry_keys,
+                            entry_inserter: #entry_inserter,
+                            element_types: #element_types,
+                            option_clearer: #option_clearer,
+                            option_default_setter: #option_default_setter,
+                            resetter: #resetter,
+                            validator: #validator,
                         }
-                    }
-                });
+                    }))
+                }).collect();
+
+                // Fields with an explicit order come first, sorted by it; the
+                // rest follow in declaration order.
+                field_info.sort_by_key(|(order, _)| order.map_or((1, 0), |order| (0, order)));
+                let field_info = field_info.into_iter().map(|(_, tokens)| tokens);
 
                 quote! {
                     vec![#(#field_info),*]
@@ -87,8 +578,8 @@ pub fn derive_config_menu(input: TokenStream) -> TokenStream {
     };
 
     let generated = quote! {
-        impl ::config_menu::ConfigMenuTrait for #name {
-            fn get_field_metadata() -> Vec<::config_menu::FieldMetadata> {
+        impl #krate::ConfigMenuTrait for #name {
+            fn get_field_metadata() -> Vec<#krate::FieldMetadata> {
                 #field_metadata
             }
 
@@ -109,6 +600,330 @@ pub fn derive_config_menu(input: TokenStream) -> TokenStream {
     TokenStream::from(generated)
 }
 
+// Resolves `ratatui-cfg` under whatever name the user's Cargo.toml gives it.
+fn crate_path() -> TokenStream2 {
+    match crate_name("ratatui-cfg") {
+        Ok(FoundCrate::Name(name)) => {
+            let ident = Ident::new(&name, Span::call_site());
+            quote! { ::#ident }
+        }
+        Ok(FoundCrate::Itself) | Err(_) => quote! { ::ratatui_cfg },
+    }
+}
+
+fn derive_enum(
+    krate: &TokenStream2,
+    name: &Ident,
+    attrs: &[Attribute],
+    data: &DataEnum,
+) -> TokenStream {
+    let serde_attrs = match parse_serde_attrs(attrs) {
+        Ok(serde_attrs) => serde_attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    // Unit variants of an untagged enum all serialize as null, so a saved
+    // value couldn't be read back as the variant that was picked.
+    if serde_attrs.untagged {
+        return syn::Error::new_spanned(
+            name,
+            "ConfigMenu can't tell the variants of an untagged enum apart once serialized",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return syn::Error::new_spanned(
+            variant,
+            "ConfigMenu only supports enums with unit variants",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let variant_idents: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let variant_names: Vec<_> = variant_idents.iter().map(|v| v.to_string()).collect();
+    // Only unit variants get here. By default one is written as its serialized
+    // name; under `tag` (with or without `content`) it's a table holding that
+    // name under the tag key, like `{ kind = "Disk" }`. Serde reads and writes
+    // that table whole, so the field's menu path is the field itself and never
+    // a path into the table. `from_variant` accepts the serialized name too so
+    // names copied from a config file parse.
+    let mut serialized_names = Vec::new();
+    for (variant, variant_name) in data.variants.iter().zip(&variant_names) {
+        let serialized_name = match parse_serde_attrs(&variant.attrs) {
+            Ok(variant_attrs) => variant_attrs.rename,
+            Err(e) => return e.to_compile_error().into(),
+        }
+        .or_else(|| {
+            serde_attrs
+                .rename_all
+                .as_deref()
+                .and_then(|rule| rename_variant(variant_name, rule))
+        })
+        .unwrap_or_else(|| variant_name.clone());
+        serialized_names.push(serialized_name);
+    }
+    let aliases: Vec<_> = serialized_names
+        .iter()
+        .zip(&variant_idents)
+        .zip(&variant_names)
+        .filter(|((serialized, _), name)| serialized != name)
+        .map(|((serialized, ident), _)| quote! { #serialized => Some(Self::#ident), })
+        .collect();
+
+    let generated = quote! {
+        impl #krate::ConfigMenuTrait for #name {
+            fn get_field_metadata() -> Vec<#krate::FieldMetadata> {
+                Vec::new()
+            }
+
+            fn get_menu_title() -> &'static str {
+                stringify!(#name)
+            }
+
+            fn as_any(&self) -> &dyn std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+                self
+            }
+
+            fn variants() -> Option<&'static [&'static str]> {
+                Some(&[#(#variant_names),*])
+            }
+
+            fn from_variant(variant: &str) -> Option<Self> {
+                match variant {
+                    #(#variant_names => Some(Self::#variant_idents),)*
+                    #(#aliases)*
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    TokenStream::from(generated)
+}
+
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    untagged: bool,
+}
+
+// The parts of `#[serde(...)]` that decide how fields and enum variants are
+// written. Everything else is skipped.
+fn parse_serde_attrs(attrs: &[Attribute]) -> syn::Result<SerdeAttrs> {
+    let mut serde_attrs = SerdeAttrs::default();
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("untagged") {
+                serde_attrs.untagged = true;
+            } else if (meta.path.is_ident("rename") || meta.path.is_ident("rename_all"))
+                && meta.input.peek(syn::Token![=])
+            {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("rename") {
+                    serde_attrs.rename = Some(lit.value());
+                } else {
+                    serde_attrs.rename_all = Some(lit.value());
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(serde_attrs)
+}
+
+// A variant name under one of serde's `rename_all` rules.
+fn rename_variant(variant: &str, rule: &str) -> Option<String> {
+    let mut words = Vec::new();
+    for c in variant.chars() {
+        if c.is_uppercase() || words.is_empty() {
+            words.push(String::new());
+        }
+        words.last_mut()?.push(c);
+    }
+    let lower: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    let upper: Vec<String> = words.iter().map(|word| word.to_uppercase()).collect();
+
+    Some(match rule {
+        "lowercase" => variant.to_lowercase(),
+        "UPPERCASE" => variant.to_uppercase(),
+        "PascalCase" => variant.to_string(),
+        "camelCase" => {
+            let mut chars = variant.chars();
+            let first = chars.next()?;
+            first.to_lowercase().chain(chars).collect()
+        }
+        "snake_case" => lower.join("_"),
+        "SCREAMING_SNAKE_CASE" => upper.join("_"),
+        "kebab-case" => lower.join("-"),
+        "SCREAMING-KEBAB-CASE" => upper.join("-"),
+        _ => return None,
+    })
+}
+
+// A field name under one of serde's `rename_all` rules. Field names are
+// snake_case, so words are split on underscores.
+fn rename_field(field: &str, rule: &str) -> Option<String> {
+    let capitalized: Vec<String> = field
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    Some(match rule {
+        "lowercase" | "snake_case" => field.to_string(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_uppercase(),
+        "PascalCase" => capitalized.concat(),
+        "camelCase" => {
+            let pascal = capitalized.concat();
+            let mut chars = pascal.chars();
+            let first = chars.next()?;
+            first.to_lowercase().chain(chars).collect()
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_uppercase().replace('_', "-"),
+        _ => return None,
+    })
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    render_markup: bool,
+    as_type: Option<String>,
+    skip: bool,
+    readonly: bool,
+    secret: bool,
+    multiline: bool,
+    default: Option<String>,
+    validate: Option<syn::Path>,
+    rename: Option<String>,
+    help: Option<String>,
+    group: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    step: Option<f64>,
+    order: Option<i64>,
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("config_menu")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("render_markup") {
+                field_attrs.render_markup = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.rename = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.default = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("validate") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.validate = Some(lit.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("group") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.group = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("help") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                field_attrs.help = Some(lit.value());
+                Ok(())
+            } else if meta.path.is_ident("min") {
+                field_attrs.min = Some(parse_bound(&meta.value()?.parse()?)?);
+                Ok(())
+            } else if meta.path.is_ident("max") {
+                field_attrs.max = Some(parse_bound(&meta.value()?.parse()?)?);
+                Ok(())
+            } else if meta.path.is_ident("step") {
+                let step = parse_bound(&meta.value()?.parse()?)?;
+                if step <= 0.0 {
+                    return Err(meta.error("step must be positive"));
+                }
+                field_attrs.step = Some(step);
+                Ok(())
+            } else if meta.path.is_ident("order") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                field_attrs.order = Some(lit.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                field_attrs.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("readonly") {
+                field_attrs.readonly = true;
+                Ok(())
+            } else if meta.path.is_ident("secret") {
+                field_attrs.secret = true;
+                Ok(())
+            } else if meta.path.is_ident("multiline") {
+                field_attrs.multiline = true;
+                Ok(())
+            } else if meta.path.is_ident("as") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                let as_type = lit.value();
+                if !is_primitive(&as_type) {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!("'{as_type}' is not a primitive type"),
+                    ));
+                }
+                field_attrs.as_type = Some(as_type);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported config_menu attribute"))
+            }
+        })?;
+    }
+
+    Ok(field_attrs)
+}
+
+fn parse_bound(expr: &syn::Expr) -> syn::Result<f64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse(),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(lit),
+            ..
+        }) => lit.base10_parse(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_bound(expr).map(|bound| -bound),
+        _ => Err(syn::Error::new_spanned(expr, "expected a numeric literal")),
+    }
+}
+
 fn analyze_type(ty: &Type) -> (bool, bool, bool, String, Option<&syn::Ident>) {
     match ty {
         Type::Path(type_path) => {
@@ -132,33 +947,172 @@ fn analyze_type(ty: &Type) -> (bool, bool, bool, String, Option<&syn::Ident>) {
                 return (nested, false, true, inner_type, inner_ident);
             }
 
-            let is_primitive = matches!(
-                ident_str.as_str(),
-                "bool"
-                    | "i8"
-                    | "i16"
-                    | "i32"
-                    | "i64"
-                    | "i128"
-                    | "u8"
-                    | "u16"
-                    | "u32"
-                    | "u64"
-                    | "u128"
-                    | "f32"
-                    | "f64"
-                    | "String"
-                    | "str"
-                    | "usize"
-                    | "isize"
-            );
-
-            if is_primitive {
+            // Maps are listed like vectors, one entry per key.
+            if let Some((_, value)) = map_types(ty) {
+                let (nested, _, _, inner_type, inner_ident) = analyze_type(value);
+                return (nested, false, true, inner_type, inner_ident);
+            }
+
+            if let Some(primitive) = nonzero_primitive(&ident_str, &last_segment.arguments) {
+                return (false, false, false, primitive, None);
+            }
+
+            if is_primitive(&ident_str) {
                 (false, false, false, ident_str, None)
             } else {
                 (true, false, false, ident_str.clone(), Some(ident))
             }
         }
+        Type::Array(array) => {
+            let (nested, _, _, inner_type, inner_ident) = analyze_type(&array.elem);
+            (nested, false, true, inner_type, inner_ident)
+        }
+        // Tuples are listed like vectors, one item per position.
+        Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            (false, false, true, "tuple".to_string(), None)
+        }
         _ => (false, false, false, "Unknown".to_string(), None),
     }
 }
+
+// Type of the elements of a `Vec`, array or map, or of the value in an
+// `Option`.
+fn element_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Array(array) => Some(&array.elem),
+        Type::Path(type_path) => {
+            if let Some((_, value)) = map_types(ty) {
+                return Some(value);
+            }
+
+            let last_segment = type_path.path.segments.last()?;
+            if !matches!(last_segment.ident.to_string().as_str(), "Option" | "Vec") {
+                return None;
+            }
+            match &last_segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    GenericArgument::Type(inner) => Some(inner),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Initial text for new values of types that don't implement Default.
+fn default_seed(ty: &Type) -> Option<&'static str> {
+    let type_path = match ty {
+        Type::Path(type_path) => type_path,
+        Type::Array(array) => return default_seed(&array.elem),
+        _ => return None,
+    };
+    let last_segment = type_path.path.segments.last()?;
+    let ident = last_segment.ident.to_string();
+
+    if matches!(ident.as_str(), "Option" | "Vec")
+        && let PathArguments::AngleBracketed(args) = &last_segment.arguments
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        return default_seed(inner);
+    }
+
+    if let Some((_, value)) = map_types(ty) {
+        return default_seed(value);
+    }
+
+    if nonzero_primitive(&ident, &last_segment.arguments).is_some() {
+        return Some("1");
+    }
+
+    match ident.as_str() {
+        "IpAddr" | "Ipv4Addr" => Some("0.0.0.0"),
+        "Ipv6Addr" => Some("::"),
+        "SocketAddr" | "SocketAddrV4" => Some("0.0.0.0:0"),
+        "SocketAddrV6" => Some("[::]:0"),
+        _ => None,
+    }
+}
+
+// Key and value types of a `HashMap<K, V>` or `BTreeMap<K, V>`.
+fn map_types(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let last_segment = type_path.path.segments.last()?;
+    if !matches!(
+        last_segment.ident.to_string().as_str(),
+        "HashMap" | "BTreeMap"
+    ) {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+        return None;
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    Some((types.next()?, types.next()?))
+}
+
+// The integer behind `NonZeroU32` and friends, or behind `NonZero<u32>`.
+fn nonzero_primitive(ident: &str, arguments: &PathArguments) -> Option<String> {
+    if ident == "NonZero"
+        && let PathArguments::AngleBracketed(args) = arguments
+        && let Some(GenericArgument::Type(Type::Path(inner))) = args.args.first()
+    {
+        return Some(inner.path.segments.last()?.ident.to_string());
+    }
+
+    let primitive = ident.strip_prefix("NonZero")?.to_lowercase();
+    matches!(
+        primitive.as_str(),
+        "i8" | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+    )
+    .then_some(primitive)
+}
+
+fn is_primitive(ident: &str) -> bool {
+    matches!(
+        ident,
+        "bool"
+            | "char"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "f32"
+            | "f64"
+            | "String"
+            | "PathBuf"
+            | "Duration"
+            | "IpAddr"
+            | "Ipv4Addr"
+            | "Ipv6Addr"
+            | "SocketAddr"
+            | "SocketAddrV4"
+            | "SocketAddrV6"
+            | "str"
+            | "usize"
+            | "isize"
+    )
+}
@@ -1,24 +1,60 @@
-#![allow(clippy::only_used_in_recursion)]
+// Lets the derive's `::ratatui_cfg` paths resolve inside this crate too.
+extern crate self as ratatui_cfg;
 
 pub use ratatui_cfg_derive::ConfigMenu;
+pub use serde_json;
 
+#[cfg(feature = "crossterm")]
+use ratatui::{
+    Terminal,
+    backend::Backend,
+    crossterm::{
+        event::{
+            DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+            KeyModifiers, MouseButton, MouseEventKind,
+        },
+        execute,
+    },
+};
 use {
-    color_eyre::eyre::{Error, Result},
+    color_eyre::eyre::{Error, Result, eyre},
     ratatui::{
         Frame,
-        layout::{Constraint, Direction, Layout, Rect},
+        layout::{Constraint, Direction, Layout, Margin, Position, Rect},
         style::{Color, Modifier, Style},
         text::{Line, Span},
-        widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+        widgets::{
+            Block, Borders, Clear, LineGauge, List, ListItem, ListState, Paragraph, Scrollbar,
+            ScrollbarOrientation, ScrollbarState,
+        },
     },
-    serde::{Deserialize, Serialize},
-    std::{any::Any, fmt::Debug, path::Path},
-    undo::{Edit, Record},
+    serde::{Deserialize, Serialize, de::DeserializeOwned},
+    serde_json::Value,
+    std::{
+        any::{Any, TypeId},
+        collections::HashMap,
+        fmt::Debug,
+        hash::{DefaultHasher, Hash, Hasher},
+        io::{Read, Write},
+        marker::PhantomData,
+        path::{Path, PathBuf},
+        sync::{LazyLock, Mutex, PoisonError},
+    },
+    undo::{Edit, Merged, Record},
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
 };
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum FieldType {
     String,
+    Char,
+    Path,
+    Addr,
+    Duration,
+    Map,
+    Tuple,
+    Array,
     Bool,
     I8,
     I16,
@@ -34,27 +70,190 @@ pub enum FieldType {
     Usize,
     F32,
     F64,
+    Enum,
     Nested,
     Unknown,
 }
 
-type Getter = Box<dyn Fn(&dyn Any) -> Option<String>>;
-type Setter = Box<dyn Fn(&mut dyn Any, String) -> Result<(), String>>;
-type NestedGetter = Box<dyn Fn(&dyn Any) -> Option<Box<dyn Any>>>;
-type NestedMetadataGetter = Box<dyn Fn() -> Vec<FieldMetadata>>;
-type NestedSetter = Box<dyn Fn(&mut dyn Any, Box<dyn Any>) -> Result<(), String>>;
+impl FieldType {
+    // Maps the type names the derive emits, and every name `Display` produces,
+    // back to a variant. Keep this in sync when adding variants. It can't fail,
+    // so it isn't worth going through `FromStr`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(name: &str) -> FieldType {
+        match name {
+            "String" | "str" => FieldType::String,
+            "char" => FieldType::Char,
+            "PathBuf" => FieldType::Path,
+            "IpAddr" | "Ipv4Addr" | "Ipv6Addr" | "SocketAddr" | "SocketAddrV4" | "SocketAddrV6"
+            | "address" => FieldType::Addr,
+            "Duration" => FieldType::Duration,
+            "map" => FieldType::Map,
+            "tuple" => FieldType::Tuple,
+            "array" => FieldType::Array,
+            "bool" => FieldType::Bool,
+            "i8" => FieldType::I8,
+            "i16" => FieldType::I16,
+            "i32" => FieldType::I32,
+            "i64" => FieldType::I64,
+            "i128" => FieldType::I128,
+            "isize" => FieldType::Isize,
+            "u8" => FieldType::U8,
+            "u16" => FieldType::U16,
+            "u32" => FieldType::U32,
+            "u64" => FieldType::U64,
+            "u128" => FieldType::U128,
+            "usize" => FieldType::Usize,
+            "f32" => FieldType::F32,
+            "f64" => FieldType::F64,
+            "enum" => FieldType::Enum,
+            "struct" => FieldType::Nested,
+            _ => FieldType::Unknown,
+        }
+    }
+
+    // The range of an integer type, except u128, whose maximum doesn't fit in
+    // an i128 and which callers handle on its own.
+    fn integer_bounds(&self) -> Option<(i128, i128)> {
+        match self {
+            FieldType::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            FieldType::I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            FieldType::I32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            FieldType::I64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            FieldType::I128 => Some((i128::MIN, i128::MAX)),
+            FieldType::Isize => Some((isize::MIN as i128, isize::MAX as i128)),
+            FieldType::U8 => Some((0, u8::MAX as i128)),
+            FieldType::U16 => Some((0, u16::MAX as i128)),
+            FieldType::U32 => Some((0, u32::MAX as i128)),
+            FieldType::U64 => Some((0, u64::MAX as i128)),
+            FieldType::Usize => Some((0, usize::MAX as i128)),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            FieldType::I8
+                | FieldType::I16
+                | FieldType::I32
+                | FieldType::I64
+                | FieldType::I128
+                | FieldType::Isize
+                | FieldType::U8
+                | FieldType::U16
+                | FieldType::U32
+                | FieldType::U64
+                | FieldType::U128
+                | FieldType::Usize
+                | FieldType::F32
+                | FieldType::F64
+        )
+    }
+}
+
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FieldType::String => "String",
+            FieldType::Char => "char",
+            FieldType::Path => "PathBuf",
+            FieldType::Addr => "address",
+            FieldType::Duration => "Duration",
+            FieldType::Map => "map",
+            FieldType::Tuple => "tuple",
+            FieldType::Array => "array",
+            FieldType::Bool => "bool",
+            FieldType::I8 => "i8",
+            FieldType::I16 => "i16",
+            FieldType::I32 => "i32",
+            FieldType::I64 => "i64",
+            FieldType::I128 => "i128",
+            FieldType::Isize => "isize",
+            FieldType::U8 => "u8",
+            FieldType::U16 => "u16",
+            FieldType::U32 => "u32",
+            FieldType::U64 => "u64",
+            FieldType::U128 => "u128",
+            FieldType::Usize => "usize",
+            FieldType::F32 => "f32",
+            FieldType::F64 => "f64",
+            FieldType::Enum => "enum",
+            FieldType::Nested => "struct",
+            FieldType::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+type Getter = Box<dyn Fn(&dyn Any) -> Option<String> + Send + Sync>;
+type Setter = Box<dyn Fn(&mut dyn Any, String) -> Result<(), String> + Send + Sync>;
+type NestedGetter = Box<dyn Fn(&dyn Any) -> Option<Box<dyn Any>> + Send + Sync>;
+type NestedMetadataGetter = Box<dyn Fn() -> &'static [FieldMetadata] + Send + Sync>;
+type NestedSetter = Box<dyn Fn(&mut dyn Any, Box<dyn Any>) -> Result<(), String> + Send + Sync>;
+type ValueGetter = Box<dyn Fn(&dyn Any) -> Option<Value> + Send + Sync>;
+type ValueSetter = Box<dyn Fn(&mut dyn Any, Value) -> Result<(), String> + Send + Sync>;
+type VecLen = Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync>;
+type ElementGetter = Box<dyn Fn(&dyn Any, usize) -> Option<String> + Send + Sync>;
+type ElementSetter = Box<dyn Fn(&mut dyn Any, usize, String) -> Result<(), String> + Send + Sync>;
+type ElementNestedGetter = Box<dyn Fn(&dyn Any, usize) -> Option<Box<dyn Any>> + Send + Sync>;
+type ElementNestedSetter =
+    Box<dyn Fn(&mut dyn Any, usize, Box<dyn Any>) -> Result<(), String> + Send + Sync>;
+type ElementPusher = Box<dyn Fn(&mut dyn Any) -> Result<(), String> + Send + Sync>;
+type ElementRemover = Box<dyn Fn(&mut dyn Any, usize) -> Result<(), String> + Send + Sync>;
+type ElementSwapper = Box<dyn Fn(&mut dyn Any, usize, usize) -> Result<(), String> + Send + Sync>;
+type EntryKeys = Box<dyn Fn(&dyn Any) -> Option<Vec<String>> + Send + Sync>;
+type EntryInserter = Box<dyn Fn(&mut dyn Any, String) -> Result<(), String> + Send + Sync>;
+type OptionClearer = Box<dyn Fn(&mut dyn Any) + Send + Sync>;
+type OptionDefaultSetter = Box<dyn Fn(&mut dyn Any) + Send + Sync>;
+type Resetter = Box<dyn Fn(&mut dyn Any) -> Result<(), String> + Send + Sync>;
+type Validator = Box<dyn Fn(&dyn Any) -> Result<(), String> + Send + Sync>;
+
+const SECRET_MASK: &str = "••••••";
 
 pub struct FieldMetadata {
     pub name: &'static str,
+    pub serialized_name: &'static str,
+    pub display_name: &'static str,
+    pub description: Option<&'static str>,
+    pub group: Option<&'static str>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    pub field_id: u64,
     pub is_nested: bool,
     pub is_option: bool,
     pub is_vec: bool,
+    pub is_map: bool,
+    pub is_array: bool,
     pub field_type: FieldType,
+    pub variants: Option<&'static [&'static str]>,
+    pub render_markup: bool,
+    pub readonly: bool,
+    pub secret: bool,
+    pub multiline: bool,
     pub getter: Getter,
     pub setter: Setter,
+    pub value_getter: ValueGetter,
+    pub value_setter: ValueSetter,
     pub nested_getter: Option<NestedGetter>,
     pub nested_metadata_getter: Option<NestedMetadataGetter>,
     pub nested_setter: Option<NestedSetter>,
+    pub vec_len: Option<VecLen>,
+    pub element_getter: Option<ElementGetter>,
+    pub element_setter: Option<ElementSetter>,
+    pub element_nested_getter: Option<ElementNestedGetter>,
+    pub element_nested_setter: Option<ElementNestedSetter>,
+    pub element_pusher: Option<ElementPusher>,
+    pub element_remover: Option<ElementRemover>,
+    pub element_swapper: Option<ElementSwapper>,
+    pub entry_keys: Option<EntryKeys>,
+    pub element_types: Option<Vec<FieldType>>,
+    pub entry_inserter: Option<EntryInserter>,
+    pub option_clearer: Option<OptionClearer>,
+    pub option_default_setter: Option<OptionDefaultSetter>,
+    pub resetter: Option<Resetter>,
+    pub validator: Option<Validator>,
 }
 
 pub trait ConfigMenuTrait: Debug + Clone + Serialize + for<'de> Deserialize<'de> + 'static {
@@ -62,21 +261,157 @@ pub trait ConfigMenuTrait: Debug + Clone + Serialize + for<'de> Deserialize<'de>
     fn get_menu_title() -> &'static str;
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn variants() -> Option<&'static [&'static str]> {
+        None
+    }
+
+    fn from_variant(_variant: &str) -> Option<Self> {
+        None
+    }
+
+    fn field_metadata() -> &'static [FieldMetadata] {
+        cached_field_metadata::<Self>()
+    }
+
+    fn field_id(field_name: &str) -> Option<u64> {
+        Self::field_metadata()
+            .iter()
+            .find(|m| m.name == field_name)
+            .map(|m| m.field_id)
+    }
+}
+
+// Metadata never changes for a type, so it's built once per process and kept
+// for the rest of the program instead of being rebuilt on every lookup.
+fn cached_field_metadata<T: ConfigMenuTrait>() -> &'static [FieldMetadata] {
+    static CACHE: LazyLock<Mutex<HashMap<TypeId, &'static [FieldMetadata]>>> =
+        LazyLock::new(Default::default);
+
+    let cached = CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&TypeId::of::<T>())
+        .copied();
+    if let Some(metadata) = cached {
+        return metadata;
+    }
+
+    // Built outside the lock, since nested types may be cached meanwhile. If
+    // another thread wins the race, its copy is kept and this one dropped.
+    let metadata = T::get_field_metadata();
+    CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::leak(metadata.into_boxed_slice()))
+}
+
+const FIELD_ID_SEED: u64 = 0xcbf2_9ce4_8422_2325;
+
+const fn extend_field_id(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+
+    hash
+}
+
+// The id of a top-level field. Fields further down are identified by
+// `field_path_id`, which gives the same id for a one-segment path.
+pub const fn stable_field_id(field_name: &str) -> u64 {
+    extend_field_id(FIELD_ID_SEED, field_name.as_bytes())
+}
+
+// Hashes the whole path, so `server.enabled` and `tls.enabled` get different
+// ids while neither depends on where the fields sit in the menu.
+pub fn field_path_id(path: &[impl AsRef<str>]) -> u64 {
+    path.iter()
+        .enumerate()
+        .fold(FIELD_ID_SEED, |hash, (i, segment)| {
+            let hash = if i == 0 {
+                hash
+            } else {
+                extend_field_id(hash, b".")
+            };
+            extend_field_id(hash, segment.as_ref().as_bytes())
+        })
 }
 
 pub fn format_field_value<T: Debug>(value: &T) -> String {
     format!("{:?}", value)
 }
 
+fn option_display(value: &str) -> String {
+    if value == "None" {
+        return "<not set>".to_string();
+    }
+
+    value
+        .strip_prefix("Some(")
+        .and_then(|inner| inner.strip_suffix(')'))
+        .unwrap_or(value)
+        .to_string()
+}
+
 fn strip_debug_quotes(s: &str) -> String {
     if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
-        let inner = &s[1..s.len() - 1];
-        inner.replace(r#"\""#, "\"").replace(r"\\", r"\")
+        unescape_debug(&s[1..s.len() - 1])
     } else {
         s.to_string()
     }
 }
 
+// Reverses the escaping done by `str::escape_debug`. Unknown escapes are kept
+// as written.
+fn unescape_debug(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let rest = chars.as_str();
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some(c @ ('\\' | '"' | '\'')) => result.push(c),
+            Some('u') => {
+                let unicode = rest
+                    .strip_prefix("u{")
+                    .and_then(|rest| rest.split_once('}'))
+                    .and_then(|(hex, tail)| {
+                        let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                        Some((c, tail))
+                    });
+                match unicode {
+                    Some((c, tail)) => {
+                        result.push(c);
+                        chars = tail.chars();
+                    }
+                    None => result.push_str("\\u"),
+                }
+            }
+            Some(c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
 pub trait ParsableField: Sized {
     fn parse_from_string(value: String) -> Result<Self, String>;
 }
@@ -95,124 +430,271 @@ impl ParsableField for bool {
     }
 }
 
-impl ParsableField for i8 {
+impl ParsableField for std::path::PathBuf {
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        Ok(value.into())
     }
 }
 
-impl ParsableField for i16 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
-    }
+macro_rules! impl_parsable_addr {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ParsableField for $ty {
+                fn parse_from_string(value: String) -> Result<Self, String> {
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("Failed to parse '{}': {}", value, e))
+                }
+            }
+        )*
+    };
 }
 
-impl ParsableField for i32 {
+impl_parsable_addr!(
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6,
+);
+
+impl ParsableField for std::time::Duration {
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        parse_duration(&value)
     }
 }
 
-impl ParsableField for i64 {
+impl ParsableField for char {
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(format!("Expected a single character, got '{}'", value)),
+        }
     }
 }
 
-impl ParsableField for i128 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
-    }
+macro_rules! impl_parsable_number {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ParsableField for $ty {
+                fn parse_from_string(value: String) -> Result<Self, String> {
+                    strip_digit_separators(&value)?
+                        .parse()
+                        .map_err(|_| format!("Failed to parse '{}'", value))
+                }
+            }
+        )*
+    };
 }
 
-impl ParsableField for isize {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
-    }
+impl_parsable_number!(f32, f64);
+
+macro_rules! impl_parsable_integer {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ParsableField for $ty {
+                fn parse_from_string(value: String) -> Result<Self, String> {
+                    let digits = strip_digit_separators(&value)?;
+                    digits.parse().map_err(|_| {
+                        if is_integer_syntax(&digits) {
+                            format!(
+                                "Value {} is out of range for {} ({}..={})",
+                                value,
+                                stringify!($ty),
+                                <$ty>::MIN,
+                                <$ty>::MAX
+                            )
+                        } else {
+                            format!("Failed to parse '{}'", value)
+                        }
+                    })
+                }
+            }
+        )*
+    };
 }
 
-impl ParsableField for u8 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
-    }
+impl_parsable_integer!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+// Whether a failed integer parse was a well-formed number that didn't fit.
+fn is_integer_syntax(digits: &str) -> bool {
+    let unsigned = digits.strip_prefix(['-', '+']).unwrap_or(digits);
+    !unsigned.is_empty() && unsigned.bytes().all(|b| b.is_ascii_digit())
 }
 
-impl ParsableField for u16 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
-    }
+macro_rules! impl_parsable_nonzero {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ParsableField for $ty {
+                fn parse_from_string(value: String) -> Result<Self, String> {
+                    let number = ParsableField::parse_from_string(value.clone())?;
+                    <$ty>::new(number).ok_or_else(|| format!("'{}' must not be zero", value))
+                }
+            }
+        )*
+    };
 }
 
-impl ParsableField for u32 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+impl_parsable_nonzero!(
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize,
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize,
+);
+
+// Underscores are always allowed between digits. Commas are only accepted
+// with the `group_digits` feature, and then only as thousands separators.
+fn strip_digit_separators(value: &str) -> Result<String, String> {
+    if !cfg!(feature = "group_digits") {
+        return Ok(value.replace('_', ""));
     }
-}
 
-impl ParsableField for u64 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+    let int_end = value.find(['.', 'e', 'E']).unwrap_or(value.len());
+    let (int_part, fraction) = value.split_at(int_end);
+
+    if fraction.contains(',') {
+        return Err(format!(
+            "Unexpected ',' after the integer part of '{}'",
+            value
+        ));
     }
-}
 
-impl ParsableField for u128 {
-    fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+    if int_part.contains(',') {
+        let digits = int_part.trim_start_matches(['-', '+']);
+        let mut groups = digits.split(',');
+        let first_ok = groups
+            .next()
+            .is_some_and(|g| (1..=3).contains(&g.len()) && g.bytes().all(|b| b.is_ascii_digit()));
+        let rest_ok = groups.all(|g| g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit()));
+
+        if !(first_ok && rest_ok) {
+            return Err(format!("Malformed digit grouping in '{}'", value));
+        }
     }
+
+    Ok(value.replace(['_', ','], ""))
 }
 
-impl ParsableField for usize {
+impl<T> ParsableField for T
+where
+    T: ConfigMenuTrait,
+{
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        if T::variants().is_some() {
+            return T::from_variant(&value).ok_or_else(|| format!("Unknown variant '{}'", value));
+        }
+
+        toml::from_str(&value).map_err(|e| format!("Failed to parse nested config: {}", e))
     }
 }
 
-impl ParsableField for f32 {
+impl<T> ParsableField for Option<T>
+where
+    T: ParsableField,
+{
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            T::parse_from_string(value).map(Some)
+        }
     }
 }
 
-impl ParsableField for f64 {
+impl<T> ParsableField for Vec<T>
+where
+    T: DeserializeOwned,
+{
     fn parse_from_string(value: String) -> Result<Self, String> {
-        value
-            .parse()
-            .map_err(|_| format!("Failed to parse '{}'", value))
+        #[derive(Deserialize)]
+        struct List<T> {
+            value: Vec<T>,
+        }
+
+        toml::from_str::<List<T>>(&format!("value = {}", value))
+            .map(|list| list.value)
+            .map_err(|e| format!("Failed to parse list: {}", e))
     }
 }
 
-impl<T> ParsableField for T
+macro_rules! impl_parsable_map {
+    ($map:ident, $($bound:path),+) => {
+        impl<K, V> ParsableField for std::collections::$map<K, V>
+        where
+            K: DeserializeOwned $(+ $bound)+,
+            V: DeserializeOwned,
+        {
+            fn parse_from_string(value: String) -> Result<Self, String> {
+                #[derive(Deserialize)]
+                struct Table<M> {
+                    value: M,
+                }
+
+                toml::from_str::<Table<Self>>(&format!("value = {}", value))
+                    .map(|table| table.value)
+                    .map_err(|e| format!("Failed to parse map: {}", e))
+            }
+        }
+    };
+}
+
+impl_parsable_map!(HashMap, Eq, std::hash::Hash);
+impl_parsable_map!(BTreeMap, Ord);
+
+macro_rules! impl_parsable_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> ParsableField for ($($name,)+)
+        where
+            $($name: DeserializeOwned,)+
+        {
+            fn parse_from_string(value: String) -> Result<Self, String> {
+                #[derive(Deserialize)]
+                struct Tuple<T> {
+                    value: T,
+                }
+
+                toml::from_str::<Tuple<Self>>(&format!("value = {}", value))
+                    .map(|tuple| tuple.value)
+                    .map_err(|e| format!("Failed to parse tuple: {}", e))
+            }
+        }
+    };
+}
+
+impl_parsable_tuple!(A, B);
+impl_parsable_tuple!(A, B, C);
+impl_parsable_tuple!(A, B, C, D);
+impl_parsable_tuple!(A, B, C, D, E);
+impl_parsable_tuple!(A, B, C, D, E, F);
+impl_parsable_tuple!(A, B, C, D, E, F, G);
+impl_parsable_tuple!(A, B, C, D, E, F, G, H);
+
+impl<T, const N: usize> ParsableField for [T; N]
 where
-    T: ConfigMenuTrait,
+    [T; N]: DeserializeOwned,
 {
     fn parse_from_string(value: String) -> Result<Self, String> {
-        toml::from_str(&value).map_err(|e| format!("Failed to parse nested config: {}", e))
+        #[derive(Deserialize)]
+        struct Array<T> {
+            value: T,
+        }
+
+        toml::from_str::<Array<Self>>(&format!("value = {}", value))
+            .map(|array| array.value)
+            .map_err(|e| format!("Failed to parse array: {}", e))
     }
 }
 
@@ -224,539 +706,3859 @@ where
     Ok(())
 }
 
-#[derive(Clone)]
-pub struct ConfigEdit {
-    _field_path: Vec<String>,
-    old_value: String,
-    new_value: String,
+// Lets the derive ask whether a type implements Default without requiring it:
+// `(&&DefaultProbe::<T>::new()).default_fn()` picks `ProbeDefault` when the
+// bound holds and falls back to `ProbeNoDefault` through auto-deref otherwise.
+#[doc(hidden)]
+pub struct DefaultProbe<T>(PhantomData<T>);
+
+impl<T> DefaultProbe<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
 }
 
-impl Edit for ConfigEdit {
-    type Target = String;
-    type Output = ();
+#[doc(hidden)]
+pub trait ProbeDefault<T> {
+    fn default_fn(&self) -> Option<fn() -> T>;
+}
 
-    fn edit(&mut self, target: &mut String) {
-        *target = self.new_value.clone();
+impl<T: Default> ProbeDefault<T> for &DefaultProbe<T> {
+    fn default_fn(&self) -> Option<fn() -> T> {
+        Some(T::default)
     }
+}
+
+#[doc(hidden)]
+pub trait ProbeNoDefault<T> {
+    fn default_fn(&self) -> Option<fn() -> T>;
+}
 
-    fn undo(&mut self, target: &mut String) {
-        *target = self.old_value.clone();
+impl<T> ProbeNoDefault<T> for DefaultProbe<T> {
+    fn default_fn(&self) -> Option<fn() -> T> {
+        None
     }
 }
 
-pub struct MenuController<T: ConfigMenuTrait> {
-    pub config: T,
-    pub menu_state: MenuState,
-    pub history: Record<ConfigEdit>,
-    pub editing_mode: bool,
-    pub edit_buffer: String,
-    pub edit_cursor: usize,
+pub fn field_to_value<T: Serialize>(field: &T) -> Option<Value> {
+    serde_json::to_value(field).ok()
 }
 
-impl<T: ConfigMenuTrait> MenuController<T> {
-    pub fn new(config: T) -> Self {
-        let menu_state = MenuState::new(&config);
-        Self {
-            config,
-            menu_state,
-            history: Record::new(),
-            editing_mode: false,
-            edit_buffer: String::new(),
-            edit_cursor: 0,
-        }
-    }
+pub fn set_from_value<T: DeserializeOwned>(field: &mut T, value: Value) -> Result<(), String> {
+    *field =
+        serde_json::from_value(value).map_err(|e| format!("Failed to restore value: {}", e))?;
+    Ok(())
+}
 
-    pub fn start_editing(&mut self) {
-        if let Some(item) = self.menu_state.get_current_item()
-            && !item.is_submenu
-            && !item.is_vec_container
-        {
-            self.editing_mode = true;
+fn parse_index(segment: &str) -> Option<usize> {
+    segment.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
 
-            if item.field_type == FieldType::String {
-                self.edit_buffer = strip_debug_quotes(&item.value);
-            } else {
-                self.edit_buffer = item.value.clone();
-            }
+// Map entries are addressed by key in paths and by position in the
+// generated closures.
+fn element_index(container: &dyn Any, field: &FieldMetadata, segment: &str) -> Option<usize> {
+    if field.is_map {
+        entry_keys(container, field)
+            .iter()
+            .position(|key| key == segment)
+    } else {
+        parse_index(segment)
+    }
+}
 
-            self.edit_cursor = self.edit_buffer.len();
-        }
+fn entry_keys(container: &dyn Any, field: &FieldMetadata) -> Vec<String> {
+    field
+        .entry_keys
+        .as_ref()
+        .and_then(|entry_keys| entry_keys(container))
+        .unwrap_or_default()
+}
+
+pub fn map_key_text<K: Serialize>(key: &K) -> String {
+    match serde_json::to_value(key) {
+        Ok(Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
     }
+}
 
-    pub fn toggle_boolean(&mut self) -> Result<(), String> {
-        if let Some(item) = self.menu_state.get_current_item()
-            && item.field_type == FieldType::Bool
-            && !item.is_submenu
-            && !item.is_vec_container
-        {
-            let new_value = if item.value == "true" {
-                "false"
-            } else {
-                "true"
-            };
+fn nested_metadata_of(field_meta: &FieldMetadata) -> Result<&'static [FieldMetadata], String> {
+    let nested_metadata_getter = field_meta
+        .nested_metadata_getter
+        .as_ref()
+        .ok_or_else(|| format!("No nested metadata getter for field '{}'", field_meta.name))?;
 
-            let field_path = self.menu_state.get_current_field_path();
-            let result = self.apply_edit_at_path(&field_path, new_value);
-
-            if result.is_ok() {
-                let current_path = self.menu_state.get_navigation_path();
-                self.menu_state = MenuState::new(&self.config);
-
-                for field_name in current_path {
-                    if let Err(e) = self
-                        .menu_state
-                        .enter_submenu_by_name(&self.config, &field_name)
-                    {
-                        eprintln!("Failed to restore navigation: {}", e);
-                        break;
-                    }
-                }
-            }
+    Ok((nested_metadata_getter)())
+}
 
-            result
-        } else {
-            Ok(())
-        }
+fn with_field_at_path<R>(
+    container: &dyn Any,
+    metadata: &[FieldMetadata],
+    field_path: &[String],
+    f: impl FnOnce(&dyn Any, &FieldMetadata, Option<usize>) -> R,
+) -> Result<R, String> {
+    let (field_name, remaining_path) = field_path
+        .split_first()
+        .ok_or_else(|| "Empty field path".to_string())?;
+
+    let field_meta = metadata
+        .iter()
+        .find(|m| m.name == field_name)
+        .ok_or_else(|| format!("Field '{}' not found", field_name))?;
+
+    if remaining_path.is_empty() {
+        return Ok(f(container, field_meta, None));
     }
 
-    pub fn finish_editing(&mut self) -> Result<(), String> {
-        if !self.editing_mode {
-            return Ok(());
+    if field_meta.is_vec {
+        let index = element_index(container, field_meta, &remaining_path[0]).ok_or_else(|| {
+            format!(
+                "No element '{}' in field '{}'",
+                remaining_path[0], field_name
+            )
+        })?;
+        let remaining_path = &remaining_path[1..];
+
+        if remaining_path.is_empty() {
+            return Ok(f(container, field_meta, Some(index)));
         }
 
-        let new_value = self.edit_buffer.clone();
-        let field_path = self.menu_state.get_current_field_path();
+        let element_getter = field_meta
+            .element_nested_getter
+            .as_ref()
+            .ok_or_else(|| format!("Elements of field '{}' are not nested", field_name))?;
 
-        let result = self.apply_edit_at_path(&field_path, &new_value);
+        let element_any = (element_getter)(container, index)
+            .ok_or_else(|| format!("No element {} in field '{}'", index, field_name))?;
 
-        if result.is_ok() {
-            let current_path = self.menu_state.get_navigation_path();
-            self.menu_state = MenuState::new(&self.config);
+        let nested_metadata = nested_metadata_of(field_meta)?;
+        return with_field_at_path(element_any.as_ref(), nested_metadata, remaining_path, f);
+    }
 
-            for field_name in current_path {
-                if let Err(e) = self
-                    .menu_state
-                    .enter_submenu_by_name(&self.config, &field_name)
-                {
-                    eprintln!("Failed to restore navigation: {}", e);
-                    break;
-                }
-            }
-        }
-
-        self.editing_mode = false;
-        result
+    if !field_meta.is_nested {
+        return Err(format!("Field '{}' is not nested", field_name));
     }
 
-    fn apply_edit_at_path(&mut self, field_path: &[String], new_value: &str) -> Result<(), String> {
-        if field_path.is_empty() {
-            return Err("Empty field path".to_string());
-        }
+    let nested_getter = field_meta
+        .nested_getter
+        .as_ref()
+        .ok_or_else(|| format!("No nested getter for field '{}'", field_name))?;
 
-        if field_path.len() == 1 {
-            Self::set_field_on_config(&mut self.config, &field_path[0], new_value)
-        } else {
-            self.set_nested_field_recursive(field_path, new_value)
-        }
-    }
+    let nested_any = (nested_getter)(container)
+        .ok_or_else(|| format!("Failed to get nested field '{}'", field_name))?;
 
-    fn set_field_on_config<U: ConfigMenuTrait>(
-        config: &mut U,
-        field_name: &str,
-        value: &str,
-    ) -> Result<(), String> {
-        let metadata = U::get_field_metadata();
-        let field_meta = metadata
-            .iter()
-            .find(|m| m.name == field_name)
-            .ok_or_else(|| format!("Field '{}' not found", field_name))?;
+    let nested_metadata = nested_metadata_of(field_meta)?;
+    with_field_at_path(nested_any.as_ref(), nested_metadata, remaining_path, f)
+}
 
-        (field_meta.setter)(config.as_any_mut(), value.to_string())
-    }
+fn update_field_at_path(
+    container: &mut dyn Any,
+    metadata: &[FieldMetadata],
+    field_path: &[String],
+    f: impl FnOnce(&mut dyn Any, &FieldMetadata, Option<usize>) -> Result<(), String>,
+) -> Result<(), String> {
+    let (field_name, remaining_path) = field_path
+        .split_first()
+        .ok_or_else(|| "Empty field path".to_string())?;
 
-    fn set_nested_field_recursive(
-        &mut self,
-        field_path: &[String],
-        new_value: &str,
-    ) -> Result<(), String> {
-        let root_field = &field_path[0];
-        let metadata = T::get_field_metadata();
+    let field_meta = metadata
+        .iter()
+        .find(|m| m.name == field_name)
+        .ok_or_else(|| format!("Field '{}' not found", field_name))?;
 
-        let field_meta = metadata
-            .iter()
-            .find(|m| m.name == root_field)
-            .ok_or_else(|| format!("Field '{}' not found", root_field))?;
+    if remaining_path.is_empty() {
+        return f(container, field_meta, None);
+    }
+
+    if field_meta.is_vec {
+        let index =
+            element_index(&*container, field_meta, &remaining_path[0]).ok_or_else(|| {
+                format!(
+                    "No element '{}' in field '{}'",
+                    remaining_path[0], field_name
+                )
+            })?;
+        let remaining_path = &remaining_path[1..];
 
-        if !field_meta.is_nested {
-            return Err(format!("Field '{}' is not nested", root_field));
+        if remaining_path.is_empty() {
+            return f(container, field_meta, Some(index));
         }
 
-        let nested_getter = field_meta
-            .nested_getter
+        let element_getter = field_meta
+            .element_nested_getter
             .as_ref()
-            .ok_or_else(|| "No nested getter available".to_string())?;
+            .ok_or_else(|| format!("Elements of field '{}' are not nested", field_name))?;
 
-        let nested_any = (nested_getter)(self.config.as_any())
-            .ok_or_else(|| format!("Failed to get nested field '{}'", root_field))?;
+        let mut element_any = (element_getter)(container, index)
+            .ok_or_else(|| format!("No element {} in field '{}'", index, field_name))?;
 
-        let updated_nested = self.update_nested_any(
-            nested_any,
-            &field_path[1..],
-            new_value,
-            field_meta.nested_metadata_getter.as_ref(),
-        )?;
+        let nested_metadata = nested_metadata_of(field_meta)?;
+        update_field_at_path(element_any.as_mut(), nested_metadata, remaining_path, f)?;
 
-        let nested_setter = field_meta
-            .nested_setter
+        let element_setter = field_meta
+            .element_nested_setter
             .as_ref()
-            .ok_or_else(|| "No nested setter available".to_string())?;
+            .ok_or_else(|| format!("No element setter for field '{}'", field_name))?;
 
-        (nested_setter)(self.config.as_any_mut(), updated_nested)
+        return (element_setter)(container, index, element_any);
     }
 
-    fn update_nested_any(
-        &self,
-        mut nested_any: Box<dyn Any>,
-        remaining_path: &[String],
-        new_value: &str,
-        metadata_getter: Option<&NestedMetadataGetter>,
-    ) -> Result<Box<dyn Any>, String> {
-        if remaining_path.is_empty() {
-            return Ok(nested_any);
-        }
-
-        let metadata =
-            metadata_getter.ok_or_else(|| "No metadata getter for nested field".to_string())?();
+    if !field_meta.is_nested {
+        return Err(format!("Field '{}' is not nested", field_name));
+    }
 
-        let field_name = &remaining_path[0];
-        let field_meta = metadata
-            .iter()
-            .find(|m| m.name == field_name)
-            .ok_or_else(|| format!("Field '{}' not found in nested structure", field_name))?;
+    let nested_getter = field_meta
+        .nested_getter
+        .as_ref()
+        .ok_or_else(|| format!("No nested getter for field '{}'", field_name))?;
 
-        if remaining_path.len() == 1 {
-            (field_meta.setter)(nested_any.as_mut(), new_value.to_string())?;
-            Ok(nested_any)
-        } else {
-            if !field_meta.is_nested {
-                return Err(format!("Field '{}' is not nested", field_name));
-            }
+    let mut nested_any = (nested_getter)(container)
+        .ok_or_else(|| format!("Failed to get nested field '{}'", field_name))?;
 
-            let inner_nested_getter = field_meta
-                .nested_getter
-                .as_ref()
-                .ok_or_else(|| "No nested getter for inner field".to_string())?;
+    let nested_metadata = nested_metadata_of(field_meta)?;
+    update_field_at_path(nested_any.as_mut(), nested_metadata, remaining_path, f)?;
 
-            let inner_nested = (inner_nested_getter)(nested_any.as_ref())
-                .ok_or_else(|| format!("Failed to get nested field '{}'", field_name))?;
+    let nested_setter = field_meta
+        .nested_setter
+        .as_ref()
+        .ok_or_else(|| format!("No nested setter for field '{}'", field_name))?;
 
-            let updated_inner = self.update_nested_any(
-                inner_nested,
-                &remaining_path[1..],
-                new_value,
-                field_meta.nested_metadata_getter.as_ref(),
-            )?;
+    (nested_setter)(container, nested_any)
+}
 
-            let inner_setter = field_meta
-                .nested_setter
-                .as_ref()
-                .ok_or_else(|| "No nested setter for inner field".to_string())?;
+fn collect_leaf_paths(
+    metadata: &[FieldMetadata],
+    prefix: &mut Vec<String>,
+    paths: &mut Vec<Vec<String>>,
+) {
+    for field in metadata {
+        prefix.push(field.name.to_string());
 
-            (inner_setter)(nested_any.as_mut(), updated_inner)?;
-            Ok(nested_any)
+        match field.nested_metadata_getter.as_ref() {
+            Some(nested_metadata_getter) if field.is_nested && !field.is_vec => {
+                collect_leaf_paths(nested_metadata_getter(), prefix, paths);
+            }
+            _ => paths.push(prefix.clone()),
         }
+
+        prefix.pop();
     }
+}
 
-    pub fn enter_submenu(&mut self) -> Result<(), String> {
-        let item = self
-            .menu_state
-            .get_current_item()
-            .ok_or_else(|| "No item selected".to_string())?;
+// The file uses serialized keys, while the collected paths use field names.
+// Unknown keys are reported as written in the file.
+fn collect_overlay_values(
+    metadata: &[FieldMetadata],
+    prefix: &mut Vec<String>,
+    key_prefix: &mut Vec<String>,
+    table: serde_json::Map<String, Value>,
+    values: &mut Vec<(Vec<String>, Value)>,
+    unknown: &mut Vec<String>,
+) {
+    for (key, value) in table {
+        let Some(field) = metadata.iter().find(|field| field.serialized_name == key) else {
+            key_prefix.push(key);
+            unknown.push(key_prefix.join("."));
+            key_prefix.pop();
+            continue;
+        };
 
-        if !item.is_submenu {
-            return Err("Current item is not a submenu".to_string());
+        prefix.push(field.name.to_string());
+        key_prefix.push(key);
+
+        match (field.nested_metadata_getter.as_ref(), value) {
+            (Some(nested_metadata_getter), Value::Object(nested_table))
+                if field.is_nested && !field.is_vec && !field.is_option =>
+            {
+                collect_overlay_values(
+                    nested_metadata_getter(),
+                    prefix,
+                    key_prefix,
+                    nested_table,
+                    values,
+                    unknown,
+                );
+            }
+            (_, value) => values.push((prefix.clone(), value)),
         }
 
-        let field_name = item.label.clone();
-        self.menu_state
-            .enter_submenu_by_name(&self.config, &field_name)
+        prefix.pop();
+        key_prefix.pop();
     }
+}
 
-    pub fn cancel_editing(&mut self) {
-        self.editing_mode = false;
-        self.edit_buffer.clear();
-        self.edit_cursor = 0;
+// Paths read better without the quotes and escapes Debug adds, and
+// durations in a compact form like `1m30s`.
+fn display_text(field_type: &FieldType, text: String) -> String {
+    match field_type {
+        FieldType::Path => strip_debug_quotes(&text),
+        FieldType::Duration => parse_duration(&text).map(format_duration).unwrap_or(text),
+        _ => text,
     }
+}
 
-    pub fn is_current_submenu(&self) -> bool {
-        self.menu_state
-            .get_current_item()
-            .is_some_and(|item| item.is_submenu)
-    }
+// Accepts plain seconds (`90`) or amounts with units (`1m30s`, `1500ms`,
+// `1.5h`), which also covers the Debug form of a Duration.
+fn parse_duration(text: &str) -> Result<std::time::Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration '{}' (expected e.g. 30s, 5m or 1500ms)",
+            text
+        )
+    };
 
-    pub fn is_current_boolean(&self) -> bool {
-        self.menu_state
-            .get_current_item()
-            .is_some_and(|item| item.field_type == FieldType::Bool && !item.is_submenu)
+    let trimmed = text.trim();
+    if let Ok(secs) = trimmed.parse::<u64>() {
+        return Ok(std::time::Duration::from_secs(secs));
     }
-
-    pub fn handle_edit_input(&mut self, c: char) {
-        self.edit_buffer.insert(self.edit_cursor, c);
-        self.edit_cursor += 1;
+    if trimmed.is_empty() {
+        return Err(invalid());
     }
 
-    pub fn handle_backspace(&mut self) {
-        if self.edit_cursor > 0 {
-            self.edit_buffer.remove(self.edit_cursor - 1);
-            self.edit_cursor -= 1;
+    let mut nanos: u128 = 0;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        let unit_end = after
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_end);
+
+        let unit_nanos: u128 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" | "min" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            "d" => 86_400_000_000_000,
+            _ => return Err(invalid()),
+        };
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        let whole: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid())?
+        };
+        let mut amount = whole.checked_mul(unit_nanos).ok_or_else(invalid)?;
+        let mut scale = unit_nanos;
+        for digit in fraction.chars() {
+            let digit = digit.to_digit(10).ok_or_else(invalid)? as u128;
+            scale /= 10;
+            amount += digit * scale;
         }
+
+        nanos = nanos.checked_add(amount).ok_or_else(invalid)?;
+        rest = after.trim_start();
     }
 
-    pub fn handle_delete(&mut self) {
-        if self.edit_cursor < self.edit_buffer.len() {
-            self.edit_buffer.remove(self.edit_cursor);
-        }
+    let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| invalid())?;
+    Ok(std::time::Duration::new(
+        secs,
+        (nanos % 1_000_000_000) as u32,
+    ))
+}
+
+fn format_duration(duration: std::time::Duration) -> String {
+    if duration.is_zero() {
+        return "0s".to_string();
     }
 
-    pub fn move_cursor_left(&mut self) {
-        if self.edit_cursor > 0 {
-            self.edit_cursor -= 1;
-        }
+    let secs = duration.as_secs();
+    let nanos = duration.subsec_nanos();
+    let parts = [
+        (secs / 86_400, "d"),
+        (secs % 86_400 / 3_600, "h"),
+        (secs % 3_600 / 60, "m"),
+        (secs % 60, "s"),
+        (u64::from(nanos / 1_000_000), "ms"),
+        (u64::from(nanos % 1_000_000 / 1_000), "µs"),
+        (u64::from(nanos % 1_000), "ns"),
+    ];
+
+    parts
+        .iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{}{}", amount, unit))
+        .collect()
+}
+
+// Tuple elements each have their own type; other containers share one.
+fn element_type(field: &FieldMetadata, index: Option<usize>) -> FieldType {
+    index
+        .and_then(|index| field.element_types.as_ref()?.get(index).cloned())
+        .unwrap_or_else(|| field.field_type.clone())
+}
+
+fn field_text(container: &dyn Any, field: &FieldMetadata, index: Option<usize>) -> Option<String> {
+    match index {
+        Some(index) => (field.element_getter.as_ref()?)(container, index),
+        None => (field.getter)(container),
     }
+}
 
-    pub fn move_cursor_right(&mut self) {
-        if self.edit_cursor < self.edit_buffer.len() {
-            self.edit_cursor += 1;
+fn set_field_text(
+    container: &mut dyn Any,
+    field: &FieldMetadata,
+    index: Option<usize>,
+    new_value: String,
+) -> Result<(), String> {
+    match index {
+        Some(index) => {
+            let element_setter = field
+                .element_setter
+                .as_ref()
+                .ok_or_else(|| format!("Field '{}' has no elements", field.name))?;
+            (element_setter)(container, index, new_value)
         }
+        None => (field.setter)(container, new_value),
     }
+}
 
-    pub fn save_to_file(&self, path: impl AsRef<Path>) -> Result<(), Error> {
-        let toml_string = toml::to_string_pretty(&self.config)?;
-        std::fs::write(path, toml_string)?;
-        Ok(())
+fn set_field_at_path(
+    container: &mut dyn Any,
+    metadata: &[FieldMetadata],
+    field_path: &[String],
+    new_value: &str,
+) -> Result<(), String> {
+    update_field_at_path(
+        container,
+        metadata,
+        field_path,
+        |container, field, index| set_field_text(container, field, index, new_value.to_string()),
+    )
+}
+
+// A Value can't hold integers outside the i64/u64 range, so i128 and u128
+// values that large are read and written as their text instead.
+fn is_wide_integer(field: &FieldMetadata, index: Option<usize>) -> bool {
+    !field.is_nested
+        && (index.is_some() || !field.is_vec)
+        && matches!(
+            element_type(field, index),
+            FieldType::I128 | FieldType::U128
+        )
+}
+
+fn read_value_at_path<T: ConfigMenuTrait>(
+    config: &T,
+    field_path: &[String],
+) -> Result<Value, String> {
+    if field_path.is_empty() {
+        return serde_json::to_value(config).map_err(|e| e.to_string());
     }
 
-    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
-        let contents = std::fs::read_to_string(path)?;
-        let config: T = toml::from_str(&contents)?;
-        Ok(Self::new(config))
+    with_field_at_path(
+        config.as_any(),
+        T::field_metadata(),
+        field_path,
+        |container, field, index| {
+            let Some(value) = (field.value_getter)(container) else {
+                return is_wide_integer(field, index)
+                    .then(|| field_text(container, field, index))
+                    .flatten()
+                    .map(Value::String)
+                    .ok_or_else(|| format!("Failed to read field '{}'", field.name));
+            };
+
+            match index {
+                Some(index) if field.is_map => entry_keys(container, field)
+                    .get(index)
+                    .and_then(|key| value.get(key))
+                    .cloned()
+                    .ok_or_else(|| format!("No element {} in field '{}'", index, field.name)),
+                Some(index) => value
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| format!("No element {} in field '{}'", index, field.name)),
+                None => Ok(value),
+            }
+        },
+    )?
+}
+
+fn write_value_at_path<T: ConfigMenuTrait>(
+    config: &mut T,
+    field_path: &[String],
+    value: Value,
+) -> Result<(), String> {
+    if field_path.is_empty() {
+        *config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        return Ok(());
     }
+
+    update_field_at_path(
+        config.as_any_mut(),
+        T::field_metadata(),
+        field_path,
+        |container, field, index| match index {
+            _ if is_wide_integer(field, index) && value.is_string() => {
+                let text = value.as_str().unwrap_or_default().to_string();
+                set_field_text(container, field, index, text)
+            }
+            Some(index) => {
+                let mut list = (field.value_getter)(&*container)
+                    .ok_or_else(|| format!("Failed to read field '{}'", field.name))?;
+                let element = if field.is_map {
+                    entry_keys(&*container, field)
+                        .get(index)
+                        .and_then(|key| list.get_mut(key))
+                } else {
+                    list.get_mut(index)
+                }
+                .ok_or_else(|| format!("No element {} in field '{}'", index, field.name))?;
+                *element = value;
+                (field.value_setter)(container, list)
+            }
+            None => (field.value_setter)(container, value),
+        },
+    )
 }
 
-pub struct MenuState {
-    pub current_selection: usize,
-    pub items: Vec<MenuItem>,
-    pub list_state: ListState,
-    pub breadcrumb: Vec<String>,
-    pub menu_stack: Vec<MenuLevel>,
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigEdit<T> {
+    field_path: Vec<String>,
+    // Derived from the path, so it's recomputed rather than saved.
+    #[serde(skip)]
+    field_id: u64,
+    old_value: Value,
+    new_value: Value,
+    #[serde(skip)]
+    _config: PhantomData<fn() -> T>,
 }
 
-pub struct MenuLevel {
-    pub items: Vec<MenuItem>,
-    pub selection: usize,
-    pub title: String,
-    pub field_path: Vec<String>,
+#[derive(Serialize, Deserialize)]
+struct SavedHistory<T> {
+    head: usize,
+    entries: Vec<ConfigEdit<T>>,
 }
 
-#[derive(Clone)]
-pub struct MenuItem {
-    pub label: String,
-    pub value: String,
-    pub is_submenu: bool,
-    pub is_vec_container: bool,
-    pub field_type: FieldType,
+impl<T: ConfigMenuTrait> ConfigEdit<T> {
+    fn new(field_path: Vec<String>, old_value: Value, new_value: Value) -> Self {
+        Self {
+            field_id: field_path_id(&field_path),
+            field_path,
+            old_value,
+            new_value,
+            _config: PhantomData,
+        }
+    }
 }
 
-impl MenuState {
-    pub fn new<T: ConfigMenuTrait>(config: &T) -> Self {
-        let metadata = T::get_field_metadata();
-        let items = Self::build_menu_items(config, &metadata);
+impl<T> ConfigEdit<T> {
+    pub fn field_path(&self) -> &[String] {
+        &self.field_path
+    }
 
-        let mut list_state = ListState::default();
-        if !items.is_empty() {
-            list_state.select(Some(0));
-        }
+    pub fn field_id(&self) -> u64 {
+        self.field_id
+    }
 
-        Self {
-            current_selection: 0,
-            items: items.clone(),
-            list_state,
-            breadcrumb: vec![T::get_menu_title().to_string()],
-            menu_stack: vec![MenuLevel {
-                items,
-                selection: 0,
-                title: T::get_menu_title().to_string(),
-                field_path: vec![],
-            }],
-        }
+    pub fn old_value(&self) -> &Value {
+        &self.old_value
     }
 
-    fn build_menu_items<T: ConfigMenuTrait>(
-        config: &T,
-        metadata: &[FieldMetadata],
-    ) -> Vec<MenuItem> {
-        metadata
-            .iter()
-            .map(|field| {
-                let value = (field.getter)(config.as_any()).unwrap_or_else(|| "N/A".to_string());
+    pub fn new_value(&self) -> &Value {
+        &self.new_value
+    }
+}
 
-                let value_display = if field.is_option {
-                    if value.contains("None") {
-                        "<not set>".to_string()
-                    } else {
-                        value.replace("Some(", "").replace(")", "")
-                    }
-                } else {
-                    value
-                };
+impl<T: ConfigMenuTrait> Edit for ConfigEdit<T> {
+    type Target = T;
+    type Output = Result<(), String>;
 
-                MenuItem {
-                    label: field.name.to_string(),
-                    value: value_display,
-                    is_submenu: field.is_nested,
-                    is_vec_container: field.is_vec,
-                    field_type: field.field_type.clone(),
-                }
-            })
-            .collect()
+    fn edit(&mut self, target: &mut T) -> Result<(), String> {
+        write_value_at_path(target, &self.field_path, self.new_value.clone())
     }
 
-    pub fn enter_submenu_by_name<T: ConfigMenuTrait>(
-        &mut self,
-        parent_config: &T,
-        field_name: &str,
-    ) -> Result<(), String> {
-        let metadata = T::get_field_metadata();
-        let field_meta = metadata
-            .iter()
-            .find(|m| m.name == field_name)
-            .ok_or_else(|| format!("Field '{}' not found", field_name))?;
+    fn undo(&mut self, target: &mut T) -> Result<(), String> {
+        write_value_at_path(target, &self.field_path, self.old_value.clone())
+    }
 
-        if !field_meta.is_nested {
-            return Err(format!("Field '{}' is not a nested structure", field_name));
+    fn merge(&mut self, other: Self) -> Merged<Self> {
+        if other.field_id != self.field_id {
+            return Merged::No(other);
         }
 
-        let nested_getter = field_meta
-            .nested_getter
-            .as_ref()
-            .ok_or_else(|| format!("No nested getter for field '{}'", field_name))?;
+        self.new_value = other.new_value;
+        if self.new_value == self.old_value {
+            Merged::Annul
+        } else {
+            Merged::Yes
+        }
+    }
+}
 
-        let nested_any = (nested_getter)(parent_config.as_any())
-            .ok_or_else(|| format!("Cannot access nested configuration for '{}'", field_name))?;
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
 
-        let nested_metadata_getter = field_meta
-            .nested_metadata_getter
-            .as_ref()
-            .ok_or_else(|| format!("No nested metadata getter for field '{}'", field_name))?;
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub field_path: Vec<String>,
+    pub old_value: String,
+    pub new_value: String,
+    pub applied: bool,
+}
 
-        let nested_metadata = (nested_metadata_getter)();
+pub struct PopupState {
+    pub title: String,
+    pub field_path: Vec<String>,
+    pub options: Vec<String>,
+    pub list_state: ListState,
+}
 
-        let nested_items = Self::build_menu_items_from_any(&*nested_any, &nested_metadata);
+#[derive(Clone, Debug)]
+pub struct MenuTheme {
+    pub breadcrumb_fg: Color,
+    pub item_fg: Color,
+    pub readonly_fg: Color,
+    pub header_fg: Color,
+    pub highlight_fg: Color,
+    pub highlight_add_modifier: Modifier,
+    pub status_fg: Color,
+    pub status_editing_fg: Color,
+    pub help_fg: Color,
+    pub highlight_symbol: String,
+    pub submenu_indicator: String,
+    pub vec_indicator: String,
+    pub checkbox_checked: String,
+    pub checkbox_unchecked: String,
+    // Columns of indentation per nesting level, and the connectors drawn in
+    // front of nested items. The defaults leave nested items unindented.
+    pub indent_width: usize,
+    pub tree_connectors: Option<TreeConnectors>,
+}
 
-        let current_level = self.menu_stack.last().unwrap();
-        let mut new_field_path = current_level.field_path.clone();
-        new_field_path.push(field_name.to_string());
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeConnectors {
+    pub branch: &'static str,
+    pub last: &'static str,
+}
 
-        let new_level = MenuLevel {
-            items: nested_items.clone(),
-            selection: 0,
-            title: field_name.to_string(),
-            field_path: new_field_path,
-        };
+impl TreeConnectors {
+    pub const ASCII: Self = Self {
+        branch: "|-",
+        last: "`-",
+    };
+    pub const UNICODE: Self = Self {
+        branch: "├─",
+        last: "└─",
+    };
+}
 
-        self.menu_stack.push(new_level);
-        self.breadcrumb.push(field_name.to_string());
-        self.items = nested_items;
-        self.current_selection = 0;
-        self.list_state.select(Some(0));
+impl Default for MenuTheme {
+    fn default() -> Self {
+        Self {
+            breadcrumb_fg: Color::Cyan,
+            item_fg: Color::White,
+            readonly_fg: Color::DarkGray,
+            header_fg: Color::Yellow,
+            highlight_fg: Color::Yellow,
+            highlight_add_modifier: Modifier::BOLD,
+            status_fg: Color::Gray,
+            status_editing_fg: Color::Green,
+            help_fg: Color::Gray,
+            highlight_symbol: ">> ".to_string(),
+            submenu_indicator: " >".to_string(),
+            vec_indicator: " []".to_string(),
+            checkbox_checked: "[x]".to_string(),
+            checkbox_unchecked: "[ ]".to_string(),
+            indent_width: 0,
+            tree_connectors: None,
+        }
+    }
+}
 
-        Ok(())
+impl MenuTheme {
+    fn highlight_style(&self) -> Style {
+        Style::default()
+            .fg(self.highlight_fg)
+            .add_modifier(self.highlight_add_modifier)
     }
+}
 
-    fn build_menu_items_from_any(
-        nested_any: &dyn Any,
-        metadata: &[FieldMetadata],
-    ) -> Vec<MenuItem> {
-        metadata
-            .iter()
-            .map(|field| {
-                let value = (field.getter)(nested_any).unwrap_or_else(|| "N/A".to_string());
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValueOverflow {
+    #[default]
+    Truncate,
+    Wrap,
+}
 
-                let value_display = if field.is_option {
-                    if value.contains("None") {
-                        "<not set>".to_string()
-                    } else {
-                        value.replace("Some(", "").replace(")", "")
-                    }
-                } else {
-                    value
-                };
+#[derive(Clone, Debug)]
+pub struct MenuLayout {
+    pub show_breadcrumb: bool,
+    pub breadcrumb_height: u16,
+    pub show_details: bool,
+    pub show_status: bool,
+    pub status_height: u16,
+    pub show_help: bool,
+    pub help_height: u16,
+    pub value_overflow: ValueOverflow,
+    pub align_values: bool,
+    pub segmented_enums: bool,
+}
 
-                MenuItem {
-                    label: field.name.to_string(),
-                    value: value_display,
-                    is_submenu: field.is_nested,
-                    is_vec_container: field.is_vec,
-                    field_type: field.field_type.clone(),
-                }
-            })
-            .collect()
+impl Default for MenuLayout {
+    fn default() -> Self {
+        Self {
+            show_breadcrumb: true,
+            breadcrumb_height: 3,
+            show_details: true,
+            show_status: true,
+            status_height: 3,
+            show_help: true,
+            help_height: 3,
+            value_overflow: ValueOverflow::default(),
+            align_values: false,
+            segmented_enums: false,
+        }
     }
+}
 
-    pub fn get_current_field_path(&self) -> Vec<String> {
-        let mut path = self
-            .menu_stack
-            .last()
-            .map(|level| level.field_path.clone())
-            .unwrap_or_default();
+// Letters for the browsing actions of `handle_key_event`, also shown in the
+// help line. Redo is `redo` pressed with Ctrl.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub quit: char,
+    pub save: char,
+    pub reload: char,
+    pub undo: char,
+    pub redo: char,
+    pub history: char,
+    pub toggle_option: char,
+    pub reset: char,
+    pub add: char,
+    pub delete: char,
+    pub search: char,
+}
 
-        if let Some(item) = self.get_current_item() {
-            path.push(item.label.clone());
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: 'q',
+            save: 's',
+            reload: 'r',
+            undo: 'u',
+            redo: 'r',
+            history: 'h',
+            toggle_option: 'n',
+            reset: 'x',
+            add: 'a',
+            delete: 'd',
+            search: '/',
         }
-
-        path
     }
+}
 
-    pub fn get_navigation_path(&self) -> Vec<String> {
-        self.menu_stack
-            .iter()
-            .skip(1)
-            .map(|level| level.title.clone())
-            .collect()
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "ron")]
+    Ron,
+}
 
-    pub fn next(&mut self) {
-        if self.items.is_empty() {
-            return;
+impl ConfigFormat {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Ok(ConfigFormat::Yaml),
+            #[cfg(not(feature = "yaml"))]
+            Some("yaml" | "yml") => Err(eyre!("YAML support requires the `yaml` feature")),
+            #[cfg(feature = "ron")]
+            Some("ron") => Ok(ConfigFormat::Ron),
+            #[cfg(not(feature = "ron"))]
+            Some("ron") => Err(eyre!("RON support requires the `ron` feature")),
+            Some(other) => Err(eyre!("Unsupported config file extension '.{}'", other)),
+            None => Err(eyre!(
+                "Cannot determine the config format of '{}'",
+                path.display()
+            )),
         }
-        let i = match self.list_state.selected() {
-            Some(i) => (i + 1) % self.items.len(),
-            None => 0,
-        };
-        self.list_state.select(Some(i));
-        self.current_selection = i;
     }
 
-    pub fn previous(&mut self) {
-        if self.items.is_empty() {
+    fn serialize<T: Serialize>(self, value: &T) -> Result<String, Error> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::to_string_pretty(value)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(value)?,
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::to_string(value)?,
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => {
+                ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?
+            }
+        })
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, contents: &str) -> Result<T, Error> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::from_str(contents)?,
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)?,
+            #[cfg(feature = "ron")]
+            ConfigFormat::Ron => ron::from_str(contents)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveConflict {
+    ExternalChange,
+    Failed(Error),
+}
+
+impl std::fmt::Display for SaveConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveConflict::ExternalChange => write!(f, "File was modified since it was loaded"),
+            SaveConflict::Failed(e) => write!(f, "Failed to save: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SaveConflict {}
+
+// Hides a typed value in a parse error. The messages either quote the value
+// or lead with it, so only those spots are replaced; a plain substring
+// replace would also hit digits in the type's bounds.
+fn mask_typed_value(message: &str, value: &str) -> String {
+    if value.is_empty() {
+        return message.to_string();
+    }
+
+    message
+        .replace(&format!("'{}'", value), &format!("'{}'", SECRET_MASK))
+        .replace(
+            &format!("Value {} is", value),
+            &format!("Value {} is", SECRET_MASK),
+        )
+}
+
+// Replaces the values of `secret` fields, including those inside nested
+// structs and list elements, with the mask shown in the menu.
+fn mask_secret_values(metadata: &[FieldMetadata], value: &mut Value) {
+    let Value::Object(table) = value else {
+        return;
+    };
+
+    for field in metadata {
+        let Some(field_value) = table.get_mut(field.serialized_name) else {
+            continue;
+        };
+
+        if field.secret {
+            *field_value = Value::String(SECRET_MASK.to_string());
+        } else if let Some(nested_metadata_getter) = &field.nested_metadata_getter {
+            let nested_metadata = nested_metadata_getter();
+            match field_value {
+                Value::Array(elements) if field.is_vec => elements
+                    .iter_mut()
+                    .for_each(|element| mask_secret_values(nested_metadata, element)),
+                Value::Object(entries) if field.is_map => entries
+                    .values_mut()
+                    .for_each(|entry| mask_secret_values(nested_metadata, entry)),
+                nested => mask_secret_values(nested_metadata, nested),
+            }
+        }
+    }
+}
+
+// Copies the values of read-only fields from `old` into `new`, so replacing a
+// whole struct leaves them as they were.
+fn keep_readonly_values(metadata: &[FieldMetadata], old: &Value, new: &mut Value) {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return;
+    };
+
+    for field in metadata {
+        let Some(old_value) = old.get(field.serialized_name) else {
+            continue;
+        };
+
+        if field.readonly {
+            new.insert(field.serialized_name.to_string(), old_value.clone());
+        } else if field.is_nested
+            && !field.is_vec
+            && let Ok(nested_metadata) = nested_metadata_of(field)
+            && let Some(new_value) = new.get_mut(field.serialized_name)
+        {
+            keep_readonly_values(nested_metadata, old_value, new_value);
+        }
+    }
+}
+
+// TOML has no null, and unset options are left out when serializing the
+// config directly, so do the same for an intermediate Value.
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(table) => {
+            table.retain(|_, value| !value.is_null());
+            table.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(elements) => elements.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("'{}' is not a file path", path.display()),
+            )
+        })?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&tmp_path, metadata.permissions())?;
+        }
+
+        std::fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+// Byte offset of the `index`th grapheme, or the end of the text.
+fn grapheme_byte_offset(text: &str, index: usize) -> usize {
+    text.grapheme_indices(true)
+        .nth(index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+// Start of the word before `cursor`, skipping any whitespace in between.
+fn previous_word_start(text: &str, cursor: usize) -> usize {
+    let before = &text[..cursor];
+    let trimmed = before.trim_end();
+    trimmed
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+// Start of the word after `cursor`, or the end of the text.
+fn next_word_start(text: &str, cursor: usize) -> usize {
+    let after = &text[cursor..];
+    let word_end = after.find(char::is_whitespace).unwrap_or(after.len());
+    let next = after[word_end..]
+        .find(|c: char| !c.is_whitespace())
+        .map_or(after.len(), |i| word_end + i);
+    cursor + next
+}
+
+pub struct MenuController<T: ConfigMenuTrait> {
+    pub config: T,
+    pub menu_state: MenuState,
+    pub history: Record<ConfigEdit<T>>,
+    pub editing_mode: bool,
+    pub edit_buffer: String,
+    // Counted in graphemes, not bytes.
+    pub edit_cursor: usize,
+    pub search_mode: bool,
+    pub new_map_key: Option<String>,
+    pub hide_values: bool,
+    pub strict_numeric_parsing: bool,
+    pub show_history: bool,
+    pub wrap_enum_cycling: bool,
+    pub popup: Option<PopupState>,
+    pub status_message: Option<String>,
+    pub backup_on_save: bool,
+    pub mask_secrets_on_export: bool,
+    pub theme: Option<MenuTheme>,
+    pub layout: MenuLayout,
+    keybindings: KeyBindings,
+    dirty_listener: Option<Box<dyn FnMut(bool)>>,
+    was_dirty: bool,
+    // Path of the most recent edit, undo or redo, for reporting key outcomes.
+    last_change: Option<Vec<String>>,
+    file_hash: Option<u64>,
+    // The file the config was last loaded from or saved to, and its format.
+    file_path: Option<PathBuf>,
+    format: ConfigFormat,
+    baseline: T,
+}
+
+impl<T: ConfigMenuTrait> MenuController<T> {
+    pub fn new(config: T) -> Self {
+        let menu_state = MenuState::new(&config);
+        Self {
+            baseline: config.clone(),
+            config,
+            menu_state,
+            history: Record::builder().limit(DEFAULT_HISTORY_LIMIT).build(),
+            editing_mode: false,
+            edit_buffer: String::new(),
+            edit_cursor: 0,
+            search_mode: false,
+            new_map_key: None,
+            hide_values: false,
+            strict_numeric_parsing: false,
+            show_history: false,
+            wrap_enum_cycling: true,
+            popup: None,
+            status_message: None,
+            backup_on_save: false,
+            mask_secrets_on_export: false,
+            theme: None,
+            layout: MenuLayout::default(),
+            keybindings: KeyBindings::default(),
+            dirty_listener: None,
+            was_dirty: false,
+            last_change: None,
+            file_hash: None,
+            file_path: None,
+            format: ConfigFormat::Toml,
+        }
+    }
+
+    pub fn with_keybindings(config: T, keybindings: KeyBindings) -> Self {
+        let mut controller = Self::new(config);
+        controller.keybindings = keybindings;
+        controller
+    }
+
+    pub fn keybindings(&self) -> &KeyBindings {
+        &self.keybindings
+    }
+
+    pub fn set_keybindings(&mut self, keybindings: KeyBindings) {
+        self.keybindings = keybindings;
+    }
+
+    pub fn start_editing(&mut self) {
+        if let Some(item) = self.menu_state.get_current_item()
+            && !item.is_submenu
+            && !item.is_vec_container
+            && !item.readonly
+            && item.field_type != FieldType::Enum
+        {
+            self.editing_mode = true;
+            self.status_message = None;
+
+            if item.multiline || item.field_type == FieldType::Char {
+                // The list shows the Debug form with quotes and escapes, so
+                // edit the raw text.
+                let field_path = self.menu_state.get_current_field_path();
+                self.edit_buffer = read_value_at_path(&self.config, &field_path)
+                    .ok()
+                    .and_then(|value| value.as_str().map(str::to_string))
+                    .unwrap_or_default();
+            } else if item.field_type == FieldType::String {
+                self.edit_buffer = strip_debug_quotes(&item.value);
+            } else {
+                self.edit_buffer = item.value.clone();
+            }
+
+            self.edit_cursor = self.edit_buffer.graphemes(true).count();
+        }
+    }
+
+    pub fn get_field(&self, path: &[impl AsRef<str>]) -> Option<String> {
+        let field_path: Vec<String> = path.iter().map(|s| s.as_ref().to_string()).collect();
+
+        // Plain values come from their serialized form, so text has no Debug
+        // quotes and optional values no `Some(..)` around them.
+        match read_value_at_path(&self.config, &field_path) {
+            Ok(Value::String(text)) => return Some(text),
+            Ok(value @ (Value::Bool(_) | Value::Number(_))) => return Some(value.to_string()),
+            _ => {}
+        }
+
+        with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            &field_path,
+            field_text,
+        )
+        .ok()
+        .flatten()
+    }
+
+    pub fn set_field(&mut self, path: &[impl AsRef<str>], value: &str) -> Result<(), String> {
+        let field_path: Vec<String> = path.iter().map(|s| s.as_ref().to_string()).collect();
+
+        self.apply_edit_at_path(&field_path, value)?;
+
+        // Replacing a whole list or struct that has a level open can change
+        // which items that level shows.
+        if self
+            .menu_state
+            .get_current_level_path()
+            .starts_with(&field_path)
+        {
+            self.rebuild_menu_state()
+        } else {
+            self.menu_state.refresh_value(&self.config, &field_path)
+        }
+    }
+
+    pub fn toggle_boolean(&mut self) -> Result<(), String> {
+        if let Some(item) = self.menu_state.get_current_item()
+            && item.field_type == FieldType::Bool
+            && !item.is_submenu
+            && !item.is_vec_container
+            && !item.readonly
+        {
+            let field_path = self.menu_state.get_current_field_path();
+            self.toggle_boolean_at_path(&field_path).map(|_| ())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn toggle_boolean_at_path(&mut self, path: &[impl AsRef<str>]) -> Result<bool, String> {
+        let field_path: Vec<String> = path.iter().map(|s| s.as_ref().to_string()).collect();
+
+        let current = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            &field_path,
+            |container, field, index| {
+                if element_type(field, index) != FieldType::Bool
+                    || field.is_nested
+                    || field.is_option
+                    || (field.is_vec && index.is_none())
+                {
+                    return Err(format!("Field '{}' is not a boolean", field.name));
+                }
+
+                field_text(container, field, index)
+                    .ok_or_else(|| format!("Failed to read field '{}'", field.name))
+            },
+        )??;
+
+        let new_value = current != "true";
+        self.apply_edit_at_path(&field_path, &new_value.to_string())?;
+        self.menu_state.refresh_value(&self.config, &field_path)?;
+        Ok(new_value)
+    }
+
+    pub fn cycle_enum(&mut self, forward: bool) -> Result<(), String> {
+        if !self.is_current_enum() || self.is_current_readonly() {
+            return Ok(());
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+        let (variants, current) = self.enum_variants_at_path(&field_path)?;
+
+        if variants.is_empty() {
+            return Ok(());
+        }
+
+        let len = variants.len();
+        let next = match current {
+            Some(i) if forward && i + 1 < len => i + 1,
+            Some(i) if !forward && i > 0 => i - 1,
+            Some(i) if !self.wrap_enum_cycling => i,
+            Some(_) if forward => 0,
+            Some(_) => len - 1,
+            None => 0,
+        };
+
+        self.apply_edit_at_path(&field_path, variants[next])?;
+        self.menu_state.refresh_value(&self.config, &field_path)
+    }
+
+    fn enum_variants_at_path(
+        &self,
+        field_path: &[String],
+    ) -> Result<(&'static [&'static str], Option<usize>), String> {
+        with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |container, field, index| {
+                let variants = field
+                    .variants
+                    .ok_or_else(|| format!("Field '{}' is not an enum", field.name))?;
+                let current = field_text(container, field, index).map(|text| {
+                    if field.is_option && index.is_none() {
+                        option_display(&text)
+                    } else {
+                        text
+                    }
+                });
+                let position = variants.iter().position(|v| Some(*v) == current.as_deref());
+                Ok((variants, position))
+            },
+        )?
+    }
+
+    pub fn open_enum_popup(&mut self) -> Result<(), String> {
+        if !self.is_current_enum() || self.is_current_readonly() {
+            return Ok(());
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+        let (variants, current) = self.enum_variants_at_path(&field_path)?;
+        let title = self
+            .menu_state
+            .get_current_item()
+            .map(|item| item.label.clone())
+            .unwrap_or_default();
+
+        let mut list_state = ListState::default();
+        list_state.select(current.or((!variants.is_empty()).then_some(0)));
+
+        self.popup = Some(PopupState {
+            title,
+            field_path,
+            options: variants.iter().map(|v| v.to_string()).collect(),
+            list_state,
+        });
+        Ok(())
+    }
+
+    pub fn popup_next(&mut self) {
+        if let Some(popup) = self.popup.as_mut()
+            && !popup.options.is_empty()
+        {
+            let i = popup
+                .list_state
+                .selected()
+                .map_or(0, |i| (i + 1) % popup.options.len());
+            popup.list_state.select(Some(i));
+        }
+    }
+
+    pub fn popup_previous(&mut self) {
+        if let Some(popup) = self.popup.as_mut()
+            && !popup.options.is_empty()
+        {
+            let len = popup.options.len();
+            let i = popup
+                .list_state
+                .selected()
+                .map_or(0, |i| (i + len - 1) % len);
+            popup.list_state.select(Some(i));
+        }
+    }
+
+    pub fn confirm_popup(&mut self) -> Result<(), String> {
+        let Some(popup) = self.popup.take() else {
+            return Ok(());
+        };
+
+        let Some(choice) = popup
+            .list_state
+            .selected()
+            .and_then(|i| popup.options.get(i))
+        else {
+            return Ok(());
+        };
+
+        self.apply_edit_at_path(&popup.field_path, choice)?;
+        self.menu_state
+            .refresh_value(&self.config, &popup.field_path)
+    }
+
+    pub fn close_popup(&mut self) {
+        self.popup = None;
+    }
+
+    pub fn toggle_option(&mut self) -> Result<(), String> {
+        if !self.is_current_option() || self.is_current_readonly() {
+            return Ok(());
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+        self.check_writable(&field_path)?;
+        let old_value = read_value_at_path(&self.config, &field_path)?;
+
+        let mut is_string = false;
+        update_field_at_path(
+            self.config.as_any_mut(),
+            T::field_metadata(),
+            &field_path,
+            |container, field, _| {
+                if !field.is_option {
+                    return Err(format!("Field '{}' is not optional", field.name));
+                }
+                let update = if old_value.is_null() {
+                    field.option_default_setter.as_ref()
+                } else {
+                    field.option_clearer.as_ref()
+                };
+                let update = update
+                    .ok_or_else(|| format!("Field '{}' has no default value to set", field.name))?;
+
+                update(container);
+                is_string = field.field_type == FieldType::String && !field.is_nested;
+                Ok(())
+            },
+        )?;
+
+        let new_value = read_value_at_path(&self.config, &field_path)?;
+        self.record_edit(&field_path, old_value.clone(), new_value)?;
+        self.menu_state.refresh_value(&self.config, &field_path)?;
+
+        if old_value.is_null() && is_string {
+            self.start_editing();
+            self.edit_buffer.clear();
+            self.edit_cursor = 0;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish_editing(&mut self) -> Result<(), String> {
+        if !self.editing_mode {
+            return Ok(());
+        }
+
+        let new_value = self.edit_buffer.clone();
+        let field_path = self.menu_state.get_current_field_path();
+
+        let result = self
+            .apply_edit_at_path(&field_path, &new_value)
+            .and_then(|_| self.menu_state.refresh_value(&self.config, &field_path));
+
+        self.editing_mode = false;
+        result
+    }
+
+    // Only needed when an edit can change which items a level shows, such as
+    // adding or removing list elements. Plain value edits go through
+    // MenuState::refresh_value instead.
+    fn rebuild_menu_state(&mut self) -> Result<(), String> {
+        let mut menu_state = MenuState::new(&self.config);
+
+        for field_name in self.menu_state.get_navigation_path() {
+            let Err(error) = menu_state.enter_submenu_by_name(&self.config, &field_name) else {
+                continue;
+            };
+
+            // Removing a list element or map entry makes its level disappear,
+            // so stop at the container. Anything else is a real failure.
+            let parent_path = menu_state.get_current_level_path();
+            let element_removed = !parent_path.is_empty()
+                && with_field_at_path(
+                    self.config.as_any(),
+                    T::field_metadata(),
+                    &parent_path,
+                    |_, field, index| field.is_vec && index.is_none(),
+                )
+                .unwrap_or(false);
+
+            if !element_removed {
+                return Err(error);
+            }
+            break;
+        }
+
+        for (level, old_level) in menu_state
+            .menu_stack
+            .iter_mut()
+            .zip(&self.menu_state.menu_stack)
+        {
+            level.selection = old_level.selection;
+        }
+
+        menu_state.visible_rows = self.menu_state.visible_rows;
+        menu_state.list_area = self.menu_state.list_area;
+        menu_state.row_items = std::mem::take(&mut self.menu_state.row_items);
+        menu_state.set_filter(self.menu_state.filter.clone());
+        self.menu_state = menu_state;
+        Ok(())
+    }
+
+    // Parsing strips digit separators, so strict mode has to reject them
+    // before the value gets that far.
+    fn check_strict_numeric(&self, field_path: &[String], value: &str) -> Result<(), String> {
+        if !self.strict_numeric_parsing || !value.contains(['_', ',']) {
+            return Ok(());
+        }
+
+        let is_numeric = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |_, field, index| element_type(field, index).is_numeric() && !field.is_nested,
+        )?;
+
+        if is_numeric {
+            return Err(format!("Failed to parse '{}'", value));
+        }
+        Ok(())
+    }
+
+    // Refuses writes to read-only fields and to anything inside one, whether
+    // they come from the menu or from the API.
+    fn check_writable(&self, field_path: &[String]) -> Result<(), String> {
+        for len in 1..=field_path.len() {
+            let readonly = with_field_at_path(
+                self.config.as_any(),
+                T::field_metadata(),
+                &field_path[..len],
+                |_, field, _| field.readonly.then_some(field.name),
+            )?;
+
+            if let Some(name) = readonly {
+                return Err(format!("Field '{}' is read-only", name));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_edit_at_path(&mut self, field_path: &[String], new_value: &str) -> Result<(), String> {
+        self.check_writable(field_path)?;
+
+        // Parse errors echo what was typed, which a secret field must not show.
+        let secret = self.is_secret_path(field_path);
+        let hide_typed = |message: String| {
+            if secret {
+                mask_typed_value(&message, new_value)
+            } else {
+                message
+            }
+        };
+
+        self.check_strict_numeric(field_path, new_value)
+            .map_err(hide_typed)?;
+
+        let old_value = read_value_at_path(&self.config, field_path)?;
+
+        if let Err(message) = set_field_at_path(
+            self.config.as_any_mut(),
+            T::field_metadata(),
+            field_path,
+            new_value,
+        ) {
+            let message = hide_typed(message);
+            self.status_message = Some(message.clone());
+            return Err(message);
+        }
+
+        let mut new_value = read_value_at_path(&self.config, field_path)?;
+
+        // Writing a whole struct mustn't change the read-only fields inside it.
+        if let Some(metadata) = self.struct_metadata_at_path(field_path)? {
+            let written = new_value.clone();
+            keep_readonly_values(metadata, &old_value, &mut new_value);
+            if new_value != written {
+                write_value_at_path(&mut self.config, field_path, new_value.clone())?;
+            }
+        }
+
+        if let Some(clamped) = self.clamp_to_bounds(field_path, &new_value)? {
+            write_value_at_path(&mut self.config, field_path, clamped.clone())?;
+            self.status_message = Some(if secret {
+                format!(
+                    "{} is out of range, clamped to {}",
+                    SECRET_MASK, SECRET_MASK
+                )
+            } else {
+                format!("{} is out of range, clamped to {}", new_value, clamped)
+            });
+            new_value = clamped;
+        }
+
+        let validation = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |container, field, _| {
+                field
+                    .validator
+                    .as_ref()
+                    .map_or(Ok(()), |validator| validator(container))
+            },
+        )?;
+
+        if let Err(message) = validation {
+            write_value_at_path(&mut self.config, field_path, old_value)?;
+            self.status_message = Some(message.clone());
+            return Err(message);
+        }
+
+        self.record_edit(field_path, old_value, new_value)
+    }
+
+    fn clamp_to_bounds(
+        &self,
+        field_path: &[String],
+        value: &Value,
+    ) -> Result<Option<Value>, String> {
+        let number = value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|text| text.parse().ok()));
+        let Some(number) = number else {
+            return Ok(None);
+        };
+
+        let (min, max) = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |_, field, _| (field.min, field.max),
+        )?;
+
+        let bound = match (min, max) {
+            (Some(min), _) if number < min => {
+                if value.is_f64() {
+                    min
+                } else {
+                    min.ceil()
+                }
+            }
+            (_, Some(max)) if number > max => {
+                if value.is_f64() {
+                    max
+                } else {
+                    max.floor()
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        let clamped = if value.is_f64() {
+            Value::from(bound)
+        } else if bound < i64::MIN as f64 {
+            Value::String((bound as i128).to_string())
+        } else if bound < 0.0 {
+            Value::from(bound as i64)
+        } else if bound > u64::MAX as f64 {
+            Value::String((bound as u128).to_string())
+        } else {
+            Value::from(bound as u64)
+        };
+        Ok(Some(clamped))
+    }
+
+    fn commit_value_at_path(
+        &mut self,
+        field_path: &[String],
+        new_value: Value,
+    ) -> Result<(), String> {
+        self.check_writable(field_path)?;
+        let old_value = read_value_at_path(&self.config, field_path)?;
+        self.record_edit(field_path, old_value, new_value)
+    }
+
+    fn record_edit(
+        &mut self,
+        field_path: &[String],
+        old_value: Value,
+        new_value: Value,
+    ) -> Result<(), String> {
+        if old_value == new_value {
+            return Ok(());
+        }
+
+        let result = self.history.edit(
+            &mut self.config,
+            ConfigEdit::new(field_path.to_vec(), old_value, new_value),
+        );
+        self.last_change = Some(field_path.to_vec());
+        self.notify_dirty_change();
+        result
+    }
+
+    pub fn on_dirty_change(&mut self, callback: impl FnMut(bool) + 'static) {
+        self.was_dirty = self.is_dirty();
+        self.dirty_listener = Some(Box::new(callback));
+    }
+
+    fn notify_dirty_change(&mut self) {
+        let dirty = self.is_dirty();
+        if dirty == self.was_dirty {
+            return;
+        }
+
+        self.was_dirty = dirty;
+        if let Some(listener) = self.dirty_listener.as_mut() {
+            listener(dirty);
+        }
+    }
+
+    // The fields of the struct at `field_path`, or None when it holds
+    // anything else.
+    fn struct_metadata_at_path(
+        &self,
+        field_path: &[String],
+    ) -> Result<Option<&'static [FieldMetadata]>, String> {
+        if field_path.is_empty() {
+            return Ok(Some(T::field_metadata()));
+        }
+
+        with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |_, field, index| {
+                (index.is_some() || !field.is_vec)
+                    .then(|| nested_metadata_of(field).ok())
+                    .flatten()
+            },
+        )
+    }
+
+    // Resets everything in the current level, including nested structs and
+    // lists below it, to its default as a single undo step. Read-only fields
+    // keep their values.
+    pub fn reset_current_level_to_default(&mut self) -> Result<(), String>
+    where
+        T: Default,
+    {
+        let level_path = self.menu_state.get_current_level_path();
+
+        let mut default_value = self.default_value_at_path(&level_path)?;
+        let current_value = read_value_at_path(&self.config, &level_path)?;
+        if let Some(metadata) = self.struct_metadata_at_path(&level_path)? {
+            keep_readonly_values(metadata, &current_value, &mut default_value);
+        }
+
+        self.commit_value_at_path(&level_path, default_value)?;
+        self.rebuild_menu_state()
+    }
+
+    // The value a level would have in a default config. List elements, map
+    // entries and options that are `None` by default have nothing to read
+    // from `T::default()`, so those start from their own type's default.
+    fn default_value_at_path(&self, field_path: &[String]) -> Result<Value, String>
+    where
+        T: Default,
+    {
+        let Some((_, parent_path)) = field_path.split_last() else {
+            return serde_json::to_value(T::default()).map_err(|e| e.to_string());
+        };
+
+        let (is_element, is_option, serialized_name) = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            field_path,
+            |_, field, index| (index.is_some(), field.is_option, field.serialized_name),
+        )?;
+
+        if is_element {
+            return self.new_element_value(field_path);
+        }
+
+        let default = self
+            .default_value_at_path(parent_path)?
+            .get(serialized_name)
+            .cloned()
+            .unwrap_or(Value::Null);
+        if !default.is_null() || !is_option {
+            return Ok(default);
+        }
+
+        let mut scratch = self.config.clone();
+        update_field_at_path(
+            scratch.as_any_mut(),
+            T::field_metadata(),
+            field_path,
+            |container, field, _| {
+                let option_default_setter = field
+                    .option_default_setter
+                    .as_ref()
+                    .ok_or_else(|| format!("Field '{}' has no default", field.name))?;
+                option_default_setter(container);
+                Ok(())
+            },
+        )?;
+        read_value_at_path(&scratch, field_path)
+    }
+
+    // Builds a fresh element on a copy of the config, the way adding one from
+    // the menu would, and returns its value.
+    fn new_element_value(&self, element_path: &[String]) -> Result<Value, String> {
+        let (segment, list_path) = element_path
+            .split_last()
+            .ok_or_else(|| "Empty field path".to_string())?;
+
+        let mut scratch = self.config.clone();
+        let mut pushed = false;
+        update_field_at_path(
+            scratch.as_any_mut(),
+            T::field_metadata(),
+            list_path,
+            |container, field, _| {
+                let no_default = || format!("Elements of field '{}' have no default", field.name);
+
+                if field.is_map {
+                    let index = element_index(&*container, field, segment).ok_or_else(|| {
+                        format!("No element '{}' in field '{}'", segment, field.name)
+                    })?;
+                    let remover = field.element_remover.as_ref().ok_or_else(no_default)?;
+                    let inserter = field.entry_inserter.as_ref().ok_or_else(no_default)?;
+                    remover(container, index)?;
+                    inserter(container, segment.clone())
+                } else if let Some(pusher) = &field.element_pusher {
+                    pushed = true;
+                    pusher(container)
+                } else {
+                    let resetter = field.resetter.as_ref().ok_or_else(no_default)?;
+                    resetter(container)
+                }
+            },
+        )?;
+
+        if !pushed {
+            return read_value_at_path(&scratch, element_path);
+        }
+
+        match read_value_at_path(&scratch, list_path)? {
+            Value::Array(mut elements) => elements
+                .pop()
+                .ok_or_else(|| format!("Failed to add an element to '{}'", list_path.join("."))),
+            _ => Err(format!("Field '{}' is not a list", list_path.join("."))),
+        }
+    }
+
+    pub fn increment_field(&mut self) -> Result<(), String> {
+        self.step_current_field(true)
+    }
+
+    pub fn decrement_field(&mut self) -> Result<(), String> {
+        self.step_current_field(false)
+    }
+
+    fn step_current_field(&mut self, up: bool) -> Result<(), String> {
+        if !self.is_current_numeric() {
+            return Ok(());
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+
+        // Stepped as text, the way the field is edited, so integers too wide
+        // for a Value still work.
+        let (current, field_type, step, min, max) = with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            &field_path,
+            |container, field, index| {
+                (
+                    field_text(container, field, index),
+                    element_type(field, index),
+                    field.step,
+                    field.min,
+                    field.max,
+                )
+            },
+        )?;
+        let Some(current) = current.filter(|text| text != "None") else {
+            return Ok(());
+        };
+        let current = option_display(&current);
+
+        let new_value = if field_type == FieldType::U128 {
+            let current: u128 = current
+                .parse()
+                .map_err(|_| format!("'{}' is not an integer", current))?;
+            let step = step.map_or(1, |step| (step.round() as u128).max(1));
+
+            // Casting a float to u128 saturates, so a negative min becomes 0.
+            let lower = min.map_or(0, |min| min.ceil() as u128);
+            let upper = max.map_or(u128::MAX, |max| max.floor() as u128);
+            let next = if up {
+                current.saturating_add(step)
+            } else {
+                current.saturating_sub(step)
+            };
+            next.clamp(lower, upper.max(lower)).to_string()
+        } else if let Some((type_min, type_max)) = field_type.integer_bounds() {
+            let current: i128 = current
+                .parse()
+                .map_err(|_| format!("'{}' is not an integer", current))?;
+            let step = step.map_or(1, |step| (step.round() as i128).max(1));
+
+            let lower = min.map_or(type_min, |min| type_min.max(min.ceil() as i128));
+            let upper = max.map_or(type_max, |max| type_max.min(max.floor() as i128));
+            let next = if up {
+                current.saturating_add(step)
+            } else {
+                current.saturating_sub(step)
+            };
+            next.clamp(lower, upper.max(lower)).to_string()
+        } else {
+            let current: f64 = current
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", current))?;
+            let step = step.unwrap_or(0.1);
+
+            // Round to the step's precision so repeated steps on an f32 don't
+            // drift into values like 0.30000001.
+            let decimals = step.to_string().split_once('.').map_or(0, |(_, d)| d.len());
+            let scale = 10f64.powi(decimals as i32);
+            let next = if up { current + step } else { current - step };
+            let mut next = (next * scale).round() / scale;
+            if let Some(min) = min {
+                next = next.max(min);
+            }
+            if let Some(max) = max {
+                next = next.min(max);
+            }
+            format!("{:.*}", decimals, next)
+        };
+
+        self.apply_edit_at_path(&field_path, &new_value)?;
+        self.menu_state.refresh_value(&self.config, &field_path)
+    }
+
+    pub fn reset_current_field(&mut self) -> Result<(), String> {
+        if !self.is_current_resettable() {
+            return Ok(());
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+        self.check_writable(&field_path)?;
+        let old_value = read_value_at_path(&self.config, &field_path)?;
+
+        update_field_at_path(
+            self.config.as_any_mut(),
+            T::field_metadata(),
+            &field_path,
+            |container, field, _| {
+                let resetter = field
+                    .resetter
+                    .as_ref()
+                    .ok_or_else(|| format!("Field '{}' has no default", field.name))?;
+                resetter(container)
+            },
+        )?;
+
+        let new_value = read_value_at_path(&self.config, &field_path)?;
+        self.record_edit(&field_path, old_value, new_value)?;
+        self.menu_state.refresh_value(&self.config, &field_path)
+    }
+
+    pub fn is_current_resettable(&self) -> bool {
+        if self.is_current_readonly() {
+            return false;
+        }
+
+        let field_path = self.menu_state.get_current_field_path();
+        with_field_at_path(
+            self.config.as_any(),
+            T::field_metadata(),
+            &field_path,
+            |_, field, index| field.resetter.is_some() && index.is_none(),
+        )
+        .unwrap_or(false)
+    }
+
+    pub fn is_in_vec_level(&self) -> bool {
+        let level_path = self.menu_state.get_current_level_path();
+        !level_path.is_empty()
+            && with_field_at_path(
+                self.config.as_any(),
+                T::field_metadata(),
+                &level_path,
+                |_, field, index| field.is_vec && index.is_none(),
+            )
+            .unwrap_or(false)
+    }
+
+    pub fn is_in_map_level(&self) -> bool {
+        self.check_vec_level(|field| field.is_map)
+    }
+
+    pub fn can_resize_elements(&self) -> bool {
+        self.check_vec_level(|field| {
+            field.element_pusher.is_some() || field.entry_inserter.is_some()
+        })
+    }
+
+    pub fn can_move_elements(&self) -> bool {
+        self.check_vec_level(|field| field.element_swapper.is_some())
+    }
+
+    fn check_vec_level(&self, check: impl FnOnce(&FieldMetadata) -> bool) -> bool {
+        let level_path = self.menu_state.get_current_level_path();
+        !level_path.is_empty()
+            && with_field_at_path(
+                self.config.as_any(),
+                T::field_metadata(),
+                &level_path,
+                |_, field, index| field.is_vec && index.is_none() && check(field),
+            )
+            .unwrap_or(false)
+    }
+
+    pub fn push_vec_element(&mut self) -> Result<(), String> {
+        self.menu_state.set_filter(None);
+        // Map entries need a key first, which is typed into a prompt.
+        if self.is_in_map_level() {
+            self.new_map_key = Some(String::new());
+            return Ok(());
+        }
+
+        self.edit_vec_at_current_level(|container, field| {
+            let element_pusher = field
+                .element_pusher
+                .as_ref()
+                .ok_or_else(|| format!("Cannot add elements to field '{}'", field.name))?;
+            (element_pusher)(container)
+        })?;
+
+        let last = self.menu_state.current_items().len().saturating_sub(1);
+        self.menu_state.select(last);
+        Ok(())
+    }
+
+    pub fn remove_vec_element(&mut self) -> Result<(), String> {
+        // Element indices refer to the whole list, not the filtered view.
+        self.menu_state.set_filter(None);
+        let index = self.menu_state.current_selection;
+        if self.menu_state.get_current_item().is_none() {
+            return Err("No element selected".to_string());
+        }
+
+        self.edit_vec_at_current_level(|container, field| {
+            let element_remover = field
+                .element_remover
+                .as_ref()
+                .ok_or_else(|| format!("Cannot remove elements from field '{}'", field.name))?;
+            (element_remover)(container, index)
+        })?;
+
+        if self.menu_state.current_items().is_empty() {
+            self.menu_state.go_back();
+        } else {
+            self.menu_state.select(index);
+        }
+        Ok(())
+    }
+
+    pub fn move_vec_element_up(&mut self) -> Result<(), String> {
+        self.menu_state.set_filter(None);
+        let index = self.menu_state.current_selection;
+        if index == 0 {
+            return Ok(());
+        }
+
+        self.swap_vec_elements(index, index - 1)
+    }
+
+    pub fn move_vec_element_down(&mut self) -> Result<(), String> {
+        self.menu_state.set_filter(None);
+        let index = self.menu_state.current_selection;
+        if index + 1 >= self.menu_state.current_items().len() {
+            return Ok(());
+        }
+
+        self.swap_vec_elements(index, index + 1)
+    }
+
+    pub fn handle_map_key_input(&mut self, c: char) {
+        if let Some(key) = &mut self.new_map_key {
+            key.push(c);
+        }
+    }
+
+    pub fn handle_map_key_backspace(&mut self) {
+        if let Some(key) = &mut self.new_map_key {
+            key.pop();
+        }
+    }
+
+    pub fn confirm_map_entry(&mut self) -> Result<(), String> {
+        let Some(key) = self.new_map_key.take() else {
+            return Ok(());
+        };
+        self.insert_map_entry(key)
+    }
+
+    pub fn cancel_map_entry(&mut self) {
+        self.new_map_key = None;
+    }
+
+    pub fn insert_map_entry(&mut self, key: String) -> Result<(), String> {
+        self.menu_state.set_filter(None);
+        let old_keys: Vec<String> = self
+            .menu_state
+            .current_items()
+            .iter()
+            .map(|item| item.key.clone())
+            .collect();
+
+        self.edit_vec_at_current_level(|container, field| {
+            let entry_inserter = field
+                .entry_inserter
+                .as_ref()
+                .ok_or_else(|| format!("Cannot add entries to field '{}'", field.name))?;
+            (entry_inserter)(container, key)
+        })?;
+
+        if let Some(index) = self
+            .menu_state
+            .current_items()
+            .iter()
+            .position(|item| !old_keys.contains(&item.key))
+        {
+            self.menu_state.select(index);
+        }
+        Ok(())
+    }
+
+    fn swap_vec_elements(&mut self, from: usize, to: usize) -> Result<(), String> {
+        self.edit_vec_at_current_level(|container, field| {
+            let element_swapper = field
+                .element_swapper
+                .as_ref()
+                .ok_or_else(|| format!("Cannot reorder elements of field '{}'", field.name))?;
+            (element_swapper)(container, from, to)
+        })?;
+
+        self.menu_state.select(to);
+        Ok(())
+    }
+
+    fn edit_vec_at_current_level(
+        &mut self,
+        edit: impl FnOnce(&mut dyn Any, &FieldMetadata) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let level_path = self.menu_state.get_current_level_path();
+        if !self.is_in_vec_level() {
+            return Err("Current menu is not a list".to_string());
+        }
+
+        let old_value = read_value_at_path(&self.config, &level_path)?;
+
+        update_field_at_path(
+            self.config.as_any_mut(),
+            T::field_metadata(),
+            &level_path,
+            |container, field, _| {
+                if field.readonly {
+                    return Err(format!("Field '{}' is read-only", field.name));
+                }
+                edit(container, field)
+            },
+        )?;
+
+        let new_value = read_value_at_path(&self.config, &level_path)?;
+        self.record_edit(&level_path, old_value, new_value)?;
+        self.rebuild_menu_state()
+    }
+
+    pub fn parse_value_at_path(&self, path: &[impl AsRef<str>], value: &str) -> Result<(), String> {
+        let field_path: Vec<String> = path.iter().map(|s| s.as_ref().to_string()).collect();
+        self.check_strict_numeric(&field_path, value)?;
+        let mut scratch = self.config.clone();
+
+        set_field_at_path(
+            scratch.as_any_mut(),
+            T::field_metadata(),
+            &field_path,
+            value,
+        )
+    }
+
+    pub fn enter_submenu(&mut self) -> Result<(), String> {
+        let item = self
+            .menu_state
+            .get_current_item()
+            .ok_or_else(|| "No item selected".to_string())?;
+
+        if !item.is_submenu && !item.is_vec_container {
+            return Err("Current item is not a submenu".to_string());
+        }
+
+        let field_name = item.key.clone();
+        self.menu_state
+            .enter_submenu_by_name(&self.config, &field_name)
+    }
+
+    pub fn history_entries(&self) -> Vec<HistoryEntry> {
+        let head = self.history.head();
+
+        self.history
+            .entries()
+            .enumerate()
+            .map(|(index, entry)| {
+                let edit = entry.as_ref();
+                HistoryEntry {
+                    field_path: edit.field_path.clone(),
+                    old_value: edit.old_value.to_string(),
+                    new_value: edit.new_value.to_string(),
+                    applied: index < head,
+                }
+            })
+            .collect()
+    }
+
+    pub fn recent_edits(&self, n: usize) -> Vec<&ConfigEdit<T>> {
+        let head = self.history.head();
+
+        self.history
+            .entries()
+            .take(head)
+            .skip(head.saturating_sub(n))
+            .map(|entry| entry.as_ref())
+            .collect()
+    }
+
+    // Moves to the state with the first `position` entries of
+    // `history_entries` applied: 0 is the config before any edit and
+    // `history_entries().len()` the latest state.
+    pub fn jump_to_history(&mut self, position: usize) -> Result<(), String> {
+        if position > self.history.len() {
+            return Err(format!("No history position {}", position));
+        }
+
+        let result = self
+            .history
+            .go_to(&mut self.config, position)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>();
+        self.notify_dirty_change();
+
+        result?;
+        self.rebuild_menu_state()
+    }
+
+    pub fn set_history_limit(&mut self, limit: usize) -> Result<(), String> {
+        let edits: Vec<ConfigEdit<T>> = self
+            .history
+            .entries()
+            .map(|entry| entry.as_ref().clone())
+            .collect();
+        let head = self.history.head();
+        let saved = self.history.saved();
+
+        self.rebuild_history(&edits, head, saved, limit.max(1))
+    }
+
+    pub fn save_history(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let saved_history = SavedHistory {
+            head: self.history.head(),
+            entries: self
+                .history
+                .entries()
+                .map(|entry| entry.as_ref().clone())
+                .collect(),
+        };
+
+        let json_string = serde_json::to_string_pretty(&saved_history)?;
+        std::fs::write(path, json_string)?;
+        Ok(())
+    }
+
+    pub fn load_history(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let contents = std::fs::read_to_string(path)?;
+        let saved_history: SavedHistory<T> = serde_json::from_str(&contents)?;
+
+        let mut head = saved_history.head.min(saved_history.entries.len());
+        let mut edits = Vec::with_capacity(saved_history.entries.len());
+        let mut skipped = Vec::new();
+
+        for (index, mut edit) in saved_history.entries.into_iter().enumerate() {
+            edit.field_id = field_path_id(&edit.field_path);
+            if read_value_at_path(&self.config, &edit.field_path).is_err() {
+                skipped.push(edit.field_path.join("."));
+                if index < saved_history.head {
+                    head -= 1;
+                }
+                continue;
+            }
+            edits.push(edit);
+        }
+
+        let saved = self.history.is_saved().then_some(head);
+        let limit = self.history.limit();
+
+        self.rebuild_history(&edits, head, saved, limit)
+            .map_err(Error::msg)?;
+
+        // Printing would corrupt the terminal, so report through the status bar.
+        if !skipped.is_empty() {
+            self.status_message = Some(format!(
+                "Skipped history for missing fields: {}",
+                skipped.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    fn rebuild_history(
+        &mut self,
+        edits: &[ConfigEdit<T>],
+        head: usize,
+        saved: Option<usize>,
+        limit: usize,
+    ) -> Result<(), String> {
+        let (first, last) = if edits.len() <= limit {
+            (0, edits.len())
+        } else {
+            (head.saturating_sub(limit), head)
+        };
+
+        // Replaying only rebuilds the record, so it runs against a copy.
+        let mut scratch = self.config.clone();
+        let mut history = Record::builder().limit(limit).build();
+
+        for edit in &edits[first..last] {
+            // A saved record never merges, which keeps replayed entries apart.
+            history.set_saved();
+            history.edit(&mut scratch, edit.clone())?;
+        }
+        history.clear_saved();
+
+        if let Some(saved) = saved
+            .and_then(|saved| saved.checked_sub(first))
+            .filter(|&saved| saved <= last - first)
+        {
+            history
+                .go_to(&mut scratch, saved)
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            history.set_saved();
+        }
+
+        history
+            .go_to(&mut scratch, head - first)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.history = history;
+        self.notify_dirty_change();
+        Ok(())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.history.is_saved()
+    }
+
+    pub fn leaf_paths(&self) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        collect_leaf_paths(T::field_metadata(), &mut Vec::new(), &mut paths);
+        paths
+    }
+
+    pub fn count_fields(&self) -> usize {
+        self.leaf_paths().len()
+    }
+
+    // Leaf paths by field id, for resolving ids kept from an earlier run or
+    // another version of the struct.
+    pub fn field_ids(&self) -> HashMap<u64, Vec<String>> {
+        self.leaf_paths()
+            .into_iter()
+            .map(|path| (field_path_id(&path), path))
+            .collect()
+    }
+
+    // Every leaf that differs from the baseline, with its current value,
+    // keyed by field id so the patch still applies after fields move.
+    pub fn diff(&self) -> HashMap<u64, Value> {
+        self.leaf_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let value = read_value_at_path(&self.config, &path).ok()?;
+                if read_value_at_path(&self.baseline, &path).ok() == Some(value.clone()) {
+                    return None;
+                }
+                Some((field_path_id(&path), value))
+            })
+            .collect()
+    }
+
+    // Writes a patch from `diff`, one undo step per field. Fails without
+    // writing anything if an id doesn't name a field of this config.
+    pub fn apply_patch(&mut self, patch: &HashMap<u64, Value>) -> Result<(), String> {
+        let ids = self.field_ids();
+        if let Some(id) = patch.keys().find(|id| !ids.contains_key(id)) {
+            return Err(format!("No field with id {:#x}", id));
+        }
+
+        for path in self.leaf_paths() {
+            if let Some(value) = patch.get(&field_path_id(&path)) {
+                self.commit_value_at_path(&path, value.clone())?;
+                self.menu_state.refresh_value(&self.config, &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn count_dirty_fields(&self) -> usize {
+        self.diff().len()
+    }
+
+    // Every leaf field that fails its validator or lies outside its
+    // min/max, with the reason, keyed by field id.
+    pub fn validation_errors(&self) -> HashMap<u64, String> {
+        self.leaf_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let error = with_field_at_path(
+                    self.config.as_any(),
+                    T::field_metadata(),
+                    &path,
+                    |container, field, _| {
+                        if let Some(validator) = field.validator.as_ref() {
+                            validator(container)?;
+                        }
+
+                        let number = match (field.value_getter)(container) {
+                            Some(value) => value.as_f64(),
+                            None if is_wide_integer(field, None) => {
+                                (field.getter)(container).and_then(|text| text.parse().ok())
+                            }
+                            None => None,
+                        };
+                        match (number, field.min, field.max) {
+                            (Some(n), Some(min), _) if n < min => {
+                                Err(format!("{} is below the minimum of {}", n, min))
+                            }
+                            (Some(n), _, Some(max)) if n > max => {
+                                Err(format!("{} is above the maximum of {}", n, max))
+                            }
+                            _ => Ok(()),
+                        }
+                    },
+                )
+                .ok()?
+                .err()?;
+                Some((field_path_id(&path), error))
+            })
+            .collect()
+    }
+
+    // `validation_errors` by path, in menu order.
+    pub fn validate_all(&self) -> Vec<(Vec<String>, String)> {
+        let mut errors = self.validation_errors();
+        self.leaf_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let error = errors.remove(&field_path_id(&path))?;
+                Some((path, error))
+            })
+            .collect()
+    }
+
+    // Opens the menu levels leading to `path` and selects its last field.
+    pub fn navigate_to(&mut self, path: &[impl AsRef<str>]) -> Result<(), String> {
+        let path: Vec<String> = path.iter().map(|p| p.as_ref().to_string()).collect();
+        let (field_name, parents) = path
+            .split_last()
+            .ok_or_else(|| "Empty field path".to_string())?;
+
+        let mut menu_state = MenuState::new(&self.config);
+        for parent in parents {
+            menu_state.enter_submenu_by_name(&self.config, parent)?;
+        }
+        let index = menu_state
+            .current_items()
+            .iter()
+            .position(|item| &item.key == field_name)
+            .ok_or_else(|| format!("Field '{}' not found", field_name))?;
+        menu_state.select(index);
+
+        menu_state.visible_rows = self.menu_state.visible_rows;
+        menu_state.list_area = self.menu_state.list_area;
+        self.menu_state = menu_state;
+        Ok(())
+    }
+
+    // Selects the next field that fails validation after the current one,
+    // wrapping around. Returns false when everything is valid.
+    pub fn goto_next_invalid(&mut self) -> bool {
+        let errors = self.validation_errors();
+        let leaves = self.leaf_paths();
+
+        // A selected submenu counts as sitting before the fields inside it.
+        let current = self.menu_state.get_current_field_path();
+        let start = leaves
+            .iter()
+            .position(|leaf| leaf.starts_with(&current))
+            .map_or(0, |i| if leaves[i] == current { i + 1 } else { i });
+
+        let next = (0..leaves.len())
+            .map(|offset| &leaves[(start + offset) % leaves.len()])
+            .find_map(|leaf| Some((leaf.clone(), errors.get(&field_path_id(leaf))?.clone())));
+
+        let Some((path, message)) = next else {
+            return false;
+        };
+        if let Err(error) = self.navigate_to(&path) {
+            self.status_message = Some(error);
+            return false;
+        }
+        self.status_message = Some(message);
+        true
+    }
+
+    pub fn one_line_summary(&self, max_width: usize) -> String {
+        let summary = T::field_metadata()
+            .iter()
+            .filter(|field| !field.is_nested)
+            .filter_map(|field| {
+                if field.secret {
+                    return Some(format!("{}={}", field.name, SECRET_MASK));
+                }
+
+                let value = match (field.value_getter)(self.config.as_any())? {
+                    Value::String(s) => s,
+                    Value::Bool(true) => "on".to_string(),
+                    Value::Bool(false) => "off".to_string(),
+                    Value::Null => "<not set>".to_string(),
+                    other => other.to_string(),
+                };
+                Some(format!("{}={}", field.name, value))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if summary.width() <= max_width {
+            return summary;
+        }
+
+        let mut truncated = take_width(&summary, max_width.saturating_sub(1)).to_string();
+        if max_width > 0 {
+            truncated.push('…');
+        }
+        truncated
+    }
+
+    pub fn undo(&mut self) -> Result<(), String> {
+        let head = self.history.head();
+        self.last_change = head
+            .checked_sub(1)
+            .and_then(|index| self.history_path(index));
+        if let Some(result) = self.history.undo(&mut self.config) {
+            self.notify_dirty_change();
+            result?;
+            self.rebuild_menu_state()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn redo(&mut self) -> Result<(), String> {
+        self.last_change = self.history_path(self.history.head());
+        if let Some(result) = self.history.redo(&mut self.config) {
+            self.notify_dirty_change();
+            result?;
+            self.rebuild_menu_state()?;
+        }
+
+        Ok(())
+    }
+
+    fn history_path(&self, index: usize) -> Option<Vec<String>> {
+        self.history
+            .entries()
+            .nth(index)
+            .map(|entry| entry.as_ref().field_path.clone())
+    }
+
+    pub fn cancel_editing(&mut self) {
+        self.editing_mode = false;
+        self.edit_buffer.clear();
+        self.edit_cursor = 0;
+    }
+
+    pub fn start_search(&mut self) {
+        self.search_mode = true;
+        let query = self.menu_state.filter.clone().unwrap_or_default();
+        self.menu_state.set_filter(Some(query));
+    }
+
+    pub fn handle_search_input(&mut self, c: char) {
+        let mut query = self.menu_state.filter.clone().unwrap_or_default();
+        query.push(c);
+        self.menu_state.set_filter(Some(query));
+    }
+
+    pub fn handle_search_backspace(&mut self) {
+        let mut query = self.menu_state.filter.clone().unwrap_or_default();
+        query.pop();
+        self.menu_state.set_filter(Some(query));
+    }
+
+    pub fn confirm_search(&mut self) {
+        self.search_mode = false;
+        if self.menu_state.filter.as_deref() == Some("") {
+            self.menu_state.set_filter(None);
+        }
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search_mode = false;
+        self.menu_state.set_filter(None);
+    }
+
+    pub fn handle_mouse(&mut self, column: u16, row: u16) -> Result<(), String> {
+        if self.editing_mode || self.popup.is_some() {
+            return Ok(());
+        }
+        let Some(index) = self.menu_state.item_at(column, row) else {
+            return Ok(());
+        };
+
+        // The first click selects an item and a click on the selected item
+        // activates it, like Enter.
+        if index != self.menu_state.current_selection {
+            self.menu_state.select(index);
+            return Ok(());
+        }
+
+        if self.is_current_enum()
+            && !self.is_current_readonly()
+            && let Some(variant) = self.enum_segment_at(column)
+        {
+            let field_path = self.menu_state.get_current_field_path();
+            self.apply_edit_at_path(&field_path, variant)?;
+            return self.menu_state.refresh_value(&self.config, &field_path);
+        }
+
+        if self.is_current_boolean() {
+            self.toggle_boolean()
+        } else if self.is_current_enum() {
+            self.open_enum_popup()
+        } else if self.is_current_submenu() {
+            self.enter_submenu()
+        } else {
+            self.start_editing();
+            Ok(())
+        }
+    }
+
+    // The variant under `column` when the selected row shows its enum as
+    // segments, laid out the way `render_menu` draws it.
+    fn enum_segment_at(&self, column: u16) -> Option<&'static str> {
+        let item = self.menu_state.get_current_item()?;
+        let theme = self.theme.clone().unwrap_or_default();
+        let list_area = self.menu_state.list_area;
+
+        let width = (list_area.width as usize).saturating_sub(2 + theme.highlight_symbol.width());
+        let items = self.menu_state.current_items();
+        let is_last = self.menu_state.current_selection + 1 == items.len();
+        let label = depth_prefix(item, is_last, &theme).width()
+            + aligned_label_width(items, &self.layout)
+                .map_or(item.label.width(), |width| width.max(item.label.width()))
+            + ": ".len();
+        let room = width.saturating_sub(label + item_indicator(item, &theme).width());
+        let (offsets, _) = enum_segments(item, &self.layout, room)?;
+
+        let start = list_area.x as usize + 1 + theme.highlight_symbol.width() + label;
+        let offset = (column as usize).checked_sub(start)?;
+        let variants = item.variants?;
+        offsets
+            .iter()
+            .zip(variants)
+            .find(|(from, variant)| (**from..**from + variant.width()).contains(&offset))
+            .map(|(_, variant)| *variant)
+    }
+
+    pub fn is_current_submenu(&self) -> bool {
+        self.menu_state
+            .get_current_item()
+            .is_some_and(|item| item.is_submenu || item.is_vec_container)
+    }
+
+    pub fn is_current_enum(&self) -> bool {
+        self.menu_state
+            .get_current_item()
+            .is_some_and(|item| item.field_type == FieldType::Enum && !item.is_vec_container)
+    }
+
+    pub fn is_current_option(&self) -> bool {
+        self.menu_state
+            .get_current_item()
+            .is_some_and(|item| item.is_option)
+    }
+
+    // Whether the path is a secret field or lies inside one.
+    fn is_secret_path(&self, field_path: &[String]) -> bool {
+        (1..=field_path.len()).any(|len| {
+            with_field_at_path(
+                self.config.as_any(),
+                T::field_metadata(),
+                &field_path[..len],
+                |_, field, _| field.secret,
+            )
+            .unwrap_or(false)
+        })
+    }
+
+    // A value written at `field_path` with the secret fields inside it
+    // masked, for showing edits that replace a whole struct or list.
+    fn masked_value_at_path(&self, field_path: &[String], value: &Value) -> Value {
+        let mut value = value.clone();
+        let (metadata, is_collection) = if field_path.is_empty() {
+            (Some(T::field_metadata()), false)
+        } else {
+            with_field_at_path(
+                self.config.as_any(),
+                T::field_metadata(),
+                field_path,
+                |_, field, index| {
+                    (
+                        nested_metadata_of(field).ok(),
+                        field.is_vec && index.is_none(),
+                    )
+                },
+            )
+            .unwrap_or((None, false))
+        };
+
+        match (metadata, &mut value) {
+            (Some(metadata), Value::Array(elements)) if is_collection => elements
+                .iter_mut()
+                .for_each(|element| mask_secret_values(metadata, element)),
+            (Some(metadata), Value::Object(entries)) if is_collection => entries
+                .values_mut()
+                .for_each(|entry| mask_secret_values(metadata, entry)),
+            (Some(metadata), value) => mask_secret_values(metadata, value),
+            (None, _) => {}
+        }
+        value
+    }
+
+    pub fn is_current_numeric(&self) -> bool {
+        self.menu_state.get_current_item().is_some_and(|item| {
+            item.field_type.is_numeric()
+                && !item.is_submenu
+                && !item.is_vec_container
+                && !item.readonly
+        })
+    }
+
+    pub fn is_current_readonly(&self) -> bool {
+        self.menu_state
+            .get_current_item()
+            .is_some_and(|item| item.readonly)
+    }
+
+    pub fn is_current_boolean(&self) -> bool {
+        self.menu_state.get_current_item().is_some_and(|item| {
+            item.field_type == FieldType::Bool && !item.is_submenu && !item.is_vec_container
+        })
+    }
+
+    fn cursor_byte_offset(&self) -> usize {
+        grapheme_byte_offset(&self.edit_buffer, self.edit_cursor)
+    }
+
+    fn set_cursor_from_byte_offset(&mut self, offset: usize) {
+        self.edit_cursor = self.edit_buffer[..offset].graphemes(true).count();
+    }
+
+    pub fn is_current_multiline(&self) -> bool {
+        self.menu_state
+            .get_current_item()
+            .is_some_and(|item| item.multiline && !item.is_submenu && !item.is_vec_container)
+    }
+
+    // Row and display column of the edit cursor, for multi-line editing.
+    pub fn edit_cursor_position(&self) -> (usize, usize) {
+        let before = &self.edit_buffer[..self.cursor_byte_offset()];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (before.matches('\n').count(), before[line_start..].width())
+    }
+
+    // Whether typing `c` at the cursor can still lead to a valid number.
+    fn accepts_numeric_char(&self, field_type: &FieldType, c: char) -> bool {
+        let before = &self.edit_buffer[..self.cursor_byte_offset()];
+        let is_float = matches!(field_type, FieldType::F32 | FieldType::F64);
+        let is_signed = is_float || field_type.integer_bounds().is_some_and(|(min, _)| min < 0);
+        let has_exponent = self.edit_buffer.contains(['e', 'E']);
+
+        match c {
+            '0'..='9' => true,
+            '_' => !self.strict_numeric_parsing,
+            ',' => cfg!(feature = "group_digits") && !self.strict_numeric_parsing,
+            '-' => {
+                (is_signed && before.is_empty() && !self.edit_buffer.starts_with('-'))
+                    || (is_float && before.ends_with(['e', 'E']))
+            }
+            '.' => is_float && !self.edit_buffer.contains('.') && !has_exponent,
+            'e' | 'E' => {
+                is_float && !has_exponent && before.ends_with(|c: char| c.is_ascii_digit())
+            }
+            _ => false,
+        }
+    }
+
+    pub fn handle_edit_input(&mut self, c: char) {
+        if let Some(item) = self.menu_state.get_current_item()
+            && item.field_type.is_numeric()
+            && !self.accepts_numeric_char(&item.field_type, c)
+        {
+            return;
+        }
+
+        // A char field holds exactly one character, so typing replaces it.
+        if self
+            .menu_state
+            .get_current_item()
+            .is_some_and(|item| item.field_type == FieldType::Char && !item.is_vec_container)
+        {
+            self.edit_buffer = c.to_string();
+            self.edit_cursor = 1;
             return;
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
+
+        let offset = self.cursor_byte_offset();
+        self.edit_buffer.insert(offset, c);
+        // A combining mark joins the grapheme before it instead of adding one.
+        self.set_cursor_from_byte_offset(offset + c.len_utf8());
+    }
+
+    pub fn handle_backspace(&mut self) {
+        if self.edit_cursor > 0 {
+            let end = self.cursor_byte_offset();
+            let start = grapheme_byte_offset(&self.edit_buffer, self.edit_cursor - 1);
+            self.edit_buffer.replace_range(start..end, "");
+            self.edit_cursor -= 1;
+        }
+    }
+
+    pub fn handle_delete(&mut self) {
+        let start = self.cursor_byte_offset();
+        let end = grapheme_byte_offset(&self.edit_buffer, self.edit_cursor + 1);
+        self.edit_buffer.replace_range(start..end, "");
+    }
+
+    pub fn delete_word_backward(&mut self) {
+        let end = self.cursor_byte_offset();
+        let start = previous_word_start(&self.edit_buffer, end);
+        self.edit_buffer.replace_range(start..end, "");
+        self.set_cursor_from_byte_offset(start);
+    }
+
+    pub fn clear_to_start(&mut self) {
+        let end = self.cursor_byte_offset();
+        self.edit_buffer.replace_range(..end, "");
+        self.edit_cursor = 0;
+    }
+
+    pub fn clear_line(&mut self) {
+        self.edit_buffer.clear();
+        self.edit_cursor = 0;
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        if self.edit_cursor > 0 {
+            self.edit_cursor -= 1;
+        }
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        if self.edit_cursor < self.edit_buffer.graphemes(true).count() {
+            self.edit_cursor += 1;
+        }
+    }
+
+    pub fn move_cursor_up(&mut self) {
+        self.move_cursor_line(false);
+    }
+
+    pub fn move_cursor_down(&mut self) {
+        self.move_cursor_line(true);
+    }
+
+    // Moves to the same grapheme column on the neighbouring line, or to
+    // that line's end when it is shorter.
+    fn move_cursor_line(&mut self, down: bool) {
+        let offset = self.cursor_byte_offset();
+        let line_start = self.edit_buffer[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let column = self.edit_buffer[line_start..offset].graphemes(true).count();
+
+        let target_start = if down {
+            match self.edit_buffer[offset..].find('\n') {
+                Some(i) => offset + i + 1,
+                None => return,
+            }
+        } else {
+            if line_start == 0 {
+                return;
+            }
+            self.edit_buffer[..line_start - 1]
+                .rfind('\n')
+                .map_or(0, |i| i + 1)
+        };
+
+        let target_line = self.edit_buffer[target_start..]
+            .split('\n')
+            .next()
+            .unwrap_or("");
+        let target = target_start + grapheme_byte_offset(target_line, column);
+        self.set_cursor_from_byte_offset(target);
+    }
+
+    pub fn move_cursor_home(&mut self) {
+        self.edit_cursor = 0;
+    }
+
+    pub fn move_cursor_end(&mut self) {
+        self.edit_cursor = self.edit_buffer.graphemes(true).count();
+    }
+
+    pub fn move_cursor_word_left(&mut self) {
+        let offset = previous_word_start(&self.edit_buffer, self.cursor_byte_offset());
+        self.set_cursor_from_byte_offset(offset);
+    }
+
+    pub fn move_cursor_word_right(&mut self) {
+        let offset = next_word_start(&self.edit_buffer, self.cursor_byte_offset());
+        self.set_cursor_from_byte_offset(offset);
+    }
+
+    pub fn format(&self) -> ConfigFormat {
+        self.format
+    }
+
+    pub fn export_current_format_string(&self) -> Result<String, Error> {
+        if !self.mask_secrets_on_export {
+            return self.format.serialize(&self.config);
+        }
+
+        let mut value = serde_json::to_value(&self.config)?;
+        mask_secret_values(T::field_metadata(), &mut value);
+        if self.format == ConfigFormat::Toml {
+            remove_nulls(&mut value);
+        }
+        self.format.serialize(&value)
+    }
+
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        self.save_as(path, ConfigFormat::from_path(path)?)
+    }
+
+    pub fn save_to_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.save_as(path, ConfigFormat::Toml)
+    }
+
+    pub fn save_to_json(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.save_as(path, ConfigFormat::Json)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn save_to_yaml(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.save_as(path, ConfigFormat::Yaml)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn save_to_ron(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.save_as(path, ConfigFormat::Ron)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.write_as(writer, ConfigFormat::Toml)
+    }
+
+    pub fn write_as<W: Write>(&self, writer: &mut W, format: ConfigFormat) -> Result<(), Error> {
+        writer.write_all(format.serialize(&self.config)?.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn save_as(&mut self, path: impl AsRef<Path>, format: ConfigFormat) -> Result<(), Error> {
+        let mut contents = Vec::new();
+        self.write_as(&mut contents, format)?;
+
+        let path = path.as_ref();
+        if self.backup_on_save && path.is_file() {
+            let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+            backup_name.push(".bak");
+            std::fs::copy(path, path.with_file_name(backup_name))?;
+        }
+
+        write_atomically(path, &contents)?;
+        self.file_path = Some(path.to_path_buf());
+        self.format = format;
+        self.mark_saved(&String::from_utf8_lossy(&contents));
+        Ok(())
+    }
+
+    fn mark_saved(&mut self, contents: &str) {
+        self.file_hash = Some(content_hash(contents));
+        self.baseline = self.config.clone();
+        self.history.set_saved();
+        self.notify_dirty_change();
+    }
+
+    // Like `save`, but refuses to overwrite a file that changed since the
+    // config was loaded or last saved. A controller that was never loaded from
+    // or saved to a file has nothing to compare against and always saves.
+    pub fn save_to_file_safe(&mut self, path: impl AsRef<Path>) -> Result<(), SaveConflict> {
+        let path = path.as_ref();
+
+        // The hash belongs to the file that was loaded or last saved, so a
+        // different path has nothing to compare against.
+        if let Some(file_hash) = self.file_hash
+            && self.file_path.as_deref() == Some(path)
+        {
+            match std::fs::read_to_string(path) {
+                Ok(contents) if content_hash(&contents) != file_hash => {
+                    return Err(SaveConflict::ExternalChange);
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(SaveConflict::Failed(e.into())),
+            }
+        }
+
+        self.save(path).map_err(SaveConflict::Failed)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        Self::load_as(path, ConfigFormat::from_path(path)?)
+    }
+
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::load_as(path, ConfigFormat::Toml)
+    }
+
+    pub fn load_from_json(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::load_as(path, ConfigFormat::Json)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn load_from_yaml(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::load_as(path, ConfigFormat::Yaml)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn load_from_ron(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::load_as(path, ConfigFormat::Ron)
+    }
+
+    // Reads the file the way `load` would and reports what `validate_all`
+    // would find in it, leaving this controller untouched.
+    pub fn validate_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<(Vec<String>, String)>, Error> {
+        Ok(Self::load(path)?.validate_all())
+    }
+
+    pub fn merge_from_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let overlay: Value = ConfigFormat::from_path(path)?.deserialize(&contents)?;
+        let Value::Object(table) = overlay else {
+            return Err(eyre!("'{}' does not contain a table", path.display()));
+        };
+
+        let mut values = Vec::new();
+        let mut unknown = Vec::new();
+        collect_overlay_values(
+            T::field_metadata(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            table,
+            &mut values,
+            &mut unknown,
+        );
+
+        let mut merged = self.config.clone();
+        let mut readonly = Vec::new();
+        for (field_path, value) in values {
+            if self.check_writable(&field_path).is_err() {
+                readonly.push(field_path.join("."));
+                continue;
+            }
+            write_value_at_path(&mut merged, &field_path, value)
+                .map_err(|e| eyre!("Failed to merge '{}': {}", field_path.join("."), e))?;
+        }
+
+        let old_value = read_value_at_path(&self.config, &[]).map_err(|e| eyre!(e))?;
+        let new_value = read_value_at_path(&merged, &[]).map_err(|e| eyre!(e))?;
+        self.record_edit(&[], old_value, new_value)
+            .and_then(|_| self.rebuild_menu_state())
+            .map_err(|e| eyre!(e))?;
+
+        let skipped: Vec<String> = [("unknown", unknown), ("read-only", readonly)]
+            .into_iter()
+            .filter(|(_, fields)| !fields.is_empty())
+            .map(|(kind, fields)| format!("{} fields: {}", kind, fields.join(", ")))
+            .collect();
+        if !skipped.is_empty() {
+            self.status_message = Some(format!("Skipped {}", skipped.join("; ")));
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::read_as(reader, ConfigFormat::Toml)
+    }
+
+    pub fn read_as<R: Read>(mut reader: R, format: ConfigFormat) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let contents = String::from_utf8(bytes)
+            .map_err(|e| eyre!("Config is not valid UTF-8: {}", e.utf8_error()))?;
+
+        let config: T = format.deserialize(&contents)?;
+        let mut controller = Self::new(config);
+        controller.file_hash = Some(content_hash(&contents));
+        controller.format = format;
+        Ok(controller)
+    }
+
+    fn load_as(path: impl AsRef<Path>, format: ConfigFormat) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut controller = Self::read_as(std::fs::File::open(path)?, format)?;
+        controller.file_path = Some(path.to_path_buf());
+        Ok(controller)
+    }
+
+    // Reads the config again from the file it was last loaded from or saved
+    // to, dropping unsaved edits and the undo history.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        let path = self
+            .file_path
+            .clone()
+            .ok_or_else(|| eyre!("The config wasn't loaded from a file"))?;
+        let loaded = Self::load_as(&path, self.format)?;
+
+        self.config = loaded.config;
+        self.baseline = loaded.baseline;
+        self.file_hash = loaded.file_hash;
+        self.history = Record::builder().limit(self.history.limit()).build();
+        if self.rebuild_menu_state().is_err() {
+            self.menu_state = loaded.menu_state;
+        }
+        self.notify_dirty_change();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "crossterm")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyOutcome {
+    Handled,
+    FieldChanged { path: Vec<String> },
+    EnteredSubmenu,
+    WentBack,
+    EditStarted,
+    EditCancelled,
+    Failed { message: String },
+    Quit,
+    Save,
+    Reload,
+    Ignored,
+}
+
+// Restores the terminal `run` ran on when it goes out of scope, so an early
+// return or a panic can't leave it in raw mode or capturing the mouse.
+#[cfg(feature = "crossterm")]
+struct RestoreTerminal;
+
+#[cfg(feature = "crossterm")]
+impl Drop for RestoreTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), DisableMouseCapture);
+        ratatui::restore();
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl<T: ConfigMenuTrait> MenuController<T> {
+    // Runs the menu on `terminal` until the user quits and returns the edited
+    // config. Mouse capture is turned on for the loop. The terminal is
+    // restored afterwards, also on errors and panics.
+    pub fn run<B: Backend>(self, terminal: &mut Terminal<B>) -> Result<T, Error> {
+        execute!(std::io::stdout(), EnableMouseCapture)?;
+        let _restore = RestoreTerminal;
+        self.run_with_events(terminal, ratatui::crossterm::event::read)
+    }
+
+    // The loop behind `run`, reading events from `next_event`. Saving and
+    // reloading use the file the config was loaded from or last saved to.
+    pub fn run_with_events<B: Backend>(
+        mut self,
+        terminal: &mut Terminal<B>,
+        mut next_event: impl FnMut() -> std::io::Result<Event>,
+    ) -> Result<T, Error> {
+        loop {
+            terminal.draw(|frame| render_menu(frame, &mut self, frame.area()))?;
+
+            match next_event()? {
+                Event::Key(key) => match self.handle_key_event(key) {
+                    KeyOutcome::Quit => return Ok(self.config),
+                    KeyOutcome::Save => {
+                        let result = match self.file_path.clone() {
+                            Some(path) => self.save_as(path, self.format),
+                            None => Err(eyre!("The config wasn't loaded from a file")),
+                        };
+                        self.status_message = Some(match result {
+                            Ok(()) => "Saved".to_string(),
+                            Err(error) => error.to_string(),
+                        });
+                    }
+                    KeyOutcome::Reload => {
+                        self.status_message = Some(match self.reload() {
+                            Ok(()) => "Reloaded".to_string(),
+                            Err(error) => error.to_string(),
+                        });
+                    }
+                    _ => {}
+                },
+                Event::Mouse(mouse) => match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        if let Err(message) = self.handle_mouse(mouse.column, mouse.row) {
+                            self.status_message = Some(message);
+                        }
+                    }
+                    MouseEventKind::ScrollUp => self.menu_state.scroll_up(),
+                    MouseEventKind::ScrollDown => self.menu_state.scroll_down(),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    // The default key scheme from the help line. Saving, reloading and quitting
+    // are only reported as `Save`/`Reload`/`Quit`; `run`/`run_with_events` act
+    // on them using the loaded file.
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> KeyOutcome {
+        if key.kind != KeyEventKind::Press {
+            return KeyOutcome::Ignored;
+        }
+
+        self.last_change = None;
+        let result = if self.popup.is_some() {
+            self.handle_popup_key(key)
+        } else if self.search_mode {
+            self.handle_search_key(key)
+        } else if self.new_map_key.is_some() {
+            self.handle_map_key(key)
+        } else if self.editing_mode {
+            self.handle_editing_key(key)
+        } else {
+            self.handle_browsing_key(key)
+        };
+
+        match result {
+            Ok(outcome) => match self.last_change.take() {
+                Some(path) => KeyOutcome::FieldChanged { path },
+                None => outcome,
+            },
+            Err(message) => {
+                self.status_message = Some(message.clone());
+                KeyOutcome::Failed { message }
+            }
+        }
+    }
+
+    fn handle_popup_key(&mut self, key: KeyEvent) -> Result<KeyOutcome, String> {
+        match key.code {
+            KeyCode::Up => self.popup_previous(),
+            KeyCode::Down => self.popup_next(),
+            KeyCode::Enter => self.confirm_popup()?,
+            KeyCode::Esc => self.close_popup(),
+            _ => return Ok(KeyOutcome::Ignored),
+        }
+        Ok(KeyOutcome::Handled)
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) -> Result<KeyOutcome, String> {
+        match key.code {
+            KeyCode::Up => self.menu_state.previous(),
+            KeyCode::Down => self.menu_state.next(),
+            KeyCode::Enter => self.confirm_search(),
+            KeyCode::Esc => self.cancel_search(),
+            KeyCode::Backspace => self.handle_search_backspace(),
+            KeyCode::Char(c) => self.handle_search_input(c),
+            _ => return Ok(KeyOutcome::Ignored),
+        }
+        Ok(KeyOutcome::Handled)
+    }
+
+    fn handle_map_key(&mut self, key: KeyEvent) -> Result<KeyOutcome, String> {
+        match key.code {
+            KeyCode::Enter => self.confirm_map_entry()?,
+            KeyCode::Esc => self.cancel_map_entry(),
+            KeyCode::Backspace => self.handle_map_key_backspace(),
+            KeyCode::Char(c) => self.handle_map_key_input(c),
+            _ => return Ok(KeyOutcome::Ignored),
+        }
+        Ok(KeyOutcome::Handled)
+    }
+
+    fn handle_editing_key(&mut self, key: KeyEvent) -> Result<KeyOutcome, String> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter
+                if self.is_current_multiline() && !key.modifiers.contains(KeyModifiers::ALT) =>
+            {
+                self.handle_edit_input('\n');
+            }
+            KeyCode::Enter => self.finish_editing()?,
+            KeyCode::Esc => {
+                self.cancel_editing();
+                return Ok(KeyOutcome::EditCancelled);
+            }
+            KeyCode::Backspace => self.handle_backspace(),
+            KeyCode::Delete => self.handle_delete(),
+            KeyCode::Left if ctrl => self.move_cursor_word_left(),
+            KeyCode::Right if ctrl => self.move_cursor_word_right(),
+            KeyCode::Left => self.move_cursor_left(),
+            KeyCode::Right => self.move_cursor_right(),
+            KeyCode::Up => self.move_cursor_up(),
+            KeyCode::Down => self.move_cursor_down(),
+            KeyCode::Home => self.move_cursor_home(),
+            KeyCode::End => self.move_cursor_end(),
+            KeyCode::Char('w') if ctrl => self.delete_word_backward(),
+            KeyCode::Char('u') if ctrl => self.clear_to_start(),
+            KeyCode::Char(_) if ctrl => return Ok(KeyOutcome::Ignored),
+            KeyCode::Char(c) => self.handle_edit_input(c),
+            _ => return Ok(KeyOutcome::Ignored),
+        }
+        Ok(KeyOutcome::Handled)
+    }
+
+    fn handle_browsing_key(&mut self, key: KeyEvent) -> Result<KeyOutcome, String> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let keys = self.keybindings.clone();
+        match key.code {
+            KeyCode::Char(c) if ctrl && c == keys.redo => self.redo()?,
+            KeyCode::Char(c) if c == keys.quit => return Ok(KeyOutcome::Quit),
+            KeyCode::Char(c) if c == keys.save => return Ok(KeyOutcome::Save),
+            KeyCode::Char(c) if c == keys.reload => return Ok(KeyOutcome::Reload),
+            KeyCode::Up if shift && self.can_move_elements() => self.move_vec_element_up()?,
+            KeyCode::Down if shift && self.can_move_elements() => self.move_vec_element_down()?,
+            KeyCode::Up => self.menu_state.previous(),
+            KeyCode::Down => self.menu_state.next(),
+            KeyCode::Home => self.menu_state.select_first(),
+            KeyCode::End => self.menu_state.select_last(),
+            KeyCode::PageUp => {
+                let page = self.menu_state.visible_rows;
+                self.menu_state.page_up(page);
+            }
+            KeyCode::PageDown => {
+                let page = self.menu_state.visible_rows;
+                self.menu_state.page_down(page);
+            }
+            KeyCode::Enter if self.is_current_boolean() => self.toggle_boolean()?,
+            KeyCode::Enter if self.is_current_enum() => self.open_enum_popup()?,
+            KeyCode::Enter if self.is_current_submenu() => {
+                self.enter_submenu()?;
+                return Ok(KeyOutcome::EnteredSubmenu);
+            }
+            KeyCode::Enter => {
+                self.start_editing();
+                if self.editing_mode {
+                    return Ok(KeyOutcome::EditStarted);
                 }
             }
-            None => 0,
+            KeyCode::Esc if self.menu_state.filter.is_some() => self.cancel_search(),
+            KeyCode::Esc if self.menu_state.can_go_back() => {
+                self.menu_state.go_back();
+                return Ok(KeyOutcome::WentBack);
+            }
+            KeyCode::Char(c) if c == keys.undo => self.undo()?,
+            KeyCode::Char(c) if c == keys.history => self.show_history = !self.show_history,
+            KeyCode::Char(c) if c == keys.toggle_option && self.is_current_option() => {
+                self.toggle_option()?
+            }
+            KeyCode::Char(' ') if self.is_current_boolean() => self.toggle_boolean()?,
+            KeyCode::Char(c) if c == keys.reset => self.reset_current_field()?,
+            KeyCode::Char(c) if c == keys.add && self.can_resize_elements() => {
+                self.push_vec_element()?
+            }
+            KeyCode::Char(c) if c == keys.delete && self.can_resize_elements() => {
+                self.remove_vec_element()?
+            }
+            KeyCode::Char(c) if c == keys.search => self.start_search(),
+            KeyCode::Char(c) if !ctrl => self.menu_state.jump_to_prefix(c),
+            KeyCode::Left if self.is_current_enum() => self.cycle_enum(false)?,
+            KeyCode::Right if self.is_current_enum() => self.cycle_enum(true)?,
+            KeyCode::Left if self.is_current_numeric() => self.decrement_field()?,
+            KeyCode::Right if self.is_current_numeric() => self.increment_field()?,
+            _ => return Ok(KeyOutcome::Ignored),
+        }
+        Ok(KeyOutcome::Handled)
+    }
+}
+
+pub struct MenuState {
+    pub current_selection: usize,
+    pub filter: Option<String>,
+    // The current level's items that match the filter, while one is set.
+    filtered_items: Option<Vec<MenuItem>>,
+    pub list_state: ListState,
+    pub breadcrumb: Vec<String>,
+    pub menu_stack: Vec<MenuLevel>,
+    // Rows the settings list had on the last render, for paging.
+    pub visible_rows: usize,
+    // Screen area of the settings list and the item shown on each of its
+    // rows, both from the last render, for mapping mouse clicks.
+    pub list_area: Rect,
+    row_items: Vec<usize>,
+}
+
+pub struct MenuLevel {
+    pub items: Vec<MenuItem>,
+    pub selection: usize,
+    pub title: String,
+    pub field_path: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct MenuItem {
+    pub key: String,
+    pub label: String,
+    pub value: String,
+    pub is_submenu: bool,
+    pub is_vec_container: bool,
+    pub is_option: bool,
+    pub field_type: FieldType,
+    pub description: Option<&'static str>,
+    pub render_markup: bool,
+    pub readonly: bool,
+    pub secret: bool,
+    pub multiline: bool,
+    pub is_header: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub variants: Option<&'static [&'static str]>,
+    // How many levels below the top-level menu the item sits.
+    pub depth: usize,
+}
+
+impl MenuItem {
+    pub fn slider_ratio(&self) -> Option<f64> {
+        let (min, max) = (self.min?, self.max?);
+        if !self.field_type.is_numeric() || self.is_vec_container || max <= min {
+            return None;
+        }
+
+        let value: f64 = self.value.parse().ok()?;
+        Some(((value - min) / (max - min)).clamp(0.0, 1.0))
+    }
+
+    pub fn type_label(&self) -> String {
+        let base = if self.is_submenu && self.field_type != FieldType::Enum {
+            FieldType::Nested.to_string()
+        } else {
+            self.field_type.to_string()
+        };
+
+        if matches!(
+            self.field_type,
+            FieldType::Map | FieldType::Tuple | FieldType::Array
+        ) {
+            return base;
+        }
+
+        match (self.is_vec_container, self.is_option) {
+            (true, true) => format!("Option<Vec<{}>>", base),
+            (true, false) => format!("Vec<{}>", base),
+            (false, true) => format!("Option<{}>", base),
+            (false, false) => base,
+        }
+    }
+}
+
+impl MenuState {
+    pub fn new<T: ConfigMenuTrait>(config: &T) -> Self {
+        let metadata = T::field_metadata();
+        let items = Self::build_menu_items_from_any(config.as_any(), metadata);
+
+        let mut menu_state = Self {
+            current_selection: 0,
+            filter: None,
+            filtered_items: None,
+            list_state: ListState::default(),
+            breadcrumb: vec![T::get_menu_title().to_string()],
+            menu_stack: vec![MenuLevel {
+                items,
+                selection: 0,
+                title: T::get_menu_title().to_string(),
+                field_path: vec![],
+            }],
+            visible_rows: 0,
+            list_area: Rect::default(),
+            row_items: Vec::new(),
+        };
+        menu_state.select(0);
+        menu_state
+    }
+
+    pub fn enter_submenu_by_name<T: ConfigMenuTrait>(
+        &mut self,
+        config: &T,
+        field_name: &str,
+    ) -> Result<(), String> {
+        let mut field_path = self.get_current_level_path();
+        field_path.push(field_name.to_string());
+
+        let nested_items = with_field_at_path(
+            config.as_any(),
+            T::field_metadata(),
+            &field_path,
+            |container, field_meta, index| {
+                let title = match index {
+                    Some(_) => field_name.to_string(),
+                    None => field_meta.display_name.to_string(),
+                };
+
+                if field_meta.is_vec && index.is_none() {
+                    return Ok((Self::build_vec_items(container, field_meta), title));
+                }
+
+                if !field_meta.is_nested {
+                    return Err(format!("Field '{}' is not a nested structure", field_name));
+                }
+
+                let nested_any = match index {
+                    Some(index) => {
+                        let element_getter =
+                            field_meta.element_nested_getter.as_ref().ok_or_else(|| {
+                                format!("No element getter for field '{}'", field_meta.name)
+                            })?;
+                        (element_getter)(container, index)
+                    }
+                    None => {
+                        let nested_getter = field_meta.nested_getter.as_ref().ok_or_else(|| {
+                            format!("No nested getter for field '{}'", field_name)
+                        })?;
+                        (nested_getter)(container)
+                    }
+                }
+                .ok_or_else(|| {
+                    format!("Cannot access nested configuration for '{}'", field_name)
+                })?;
+
+                let nested_metadata = nested_metadata_of(field_meta)?;
+
+                Ok((
+                    Self::build_menu_items_from_any(&*nested_any, nested_metadata),
+                    title,
+                ))
+            },
+        )??;
+        let (mut nested_items, title) = nested_items;
+        for item in &mut nested_items {
+            item.depth = field_path.len();
+        }
+
+        // Remember where we were so going back returns to the same field,
+        // even if it was picked from a filtered view.
+        let selected_key = self.get_current_item().map(|item| item.key.clone());
+        if let Some(level) = self.menu_stack.last_mut() {
+            level.selection = selected_key
+                .and_then(|key| level.items.iter().position(|item| item.key == key))
+                .unwrap_or(0);
+        }
+
+        self.menu_stack.push(MenuLevel {
+            items: nested_items,
+            selection: 0,
+            title: title.clone(),
+            field_path,
+        });
+        self.breadcrumb.push(title);
+        self.filter = None;
+        self.filtered_items = None;
+        self.select(0);
+
+        Ok(())
+    }
+
+    fn build_menu_items_from_any(
+        nested_any: &dyn Any,
+        metadata: &[FieldMetadata],
+    ) -> Vec<MenuItem> {
+        if metadata.iter().any(|field| field.group.is_some()) {
+            return Self::build_grouped_items(nested_any, metadata);
+        }
+
+        metadata
+            .iter()
+            .map(|field| Self::build_menu_item(nested_any, field))
+            .collect()
+    }
+
+    fn item_value(container: &dyn Any, field: &FieldMetadata, index: Option<usize>) -> String {
+        let Some(value) = field_text(container, field, index) else {
+            return "N/A".to_string();
+        };
+
+        let value = if field.is_option && index.is_none() {
+            option_display(&value)
+        } else {
+            value
+        };
+        display_text(&element_type(field, index), value)
+    }
+
+    fn build_menu_item(nested_any: &dyn Any, field: &FieldMetadata) -> MenuItem {
+        MenuItem {
+            key: field.name.to_string(),
+            label: field.display_name.to_string(),
+            value: Self::item_value(nested_any, field, None),
+            is_submenu: field.is_nested && !field.is_vec,
+            is_vec_container: field.is_vec,
+            is_option: field.is_option,
+            field_type: if field.is_map {
+                FieldType::Map
+            } else if field.is_array {
+                FieldType::Array
+            } else {
+                field.field_type.clone()
+            },
+            description: field.description,
+            render_markup: field.render_markup,
+            readonly: field.readonly,
+            secret: field.secret,
+            multiline: field.multiline,
+            is_header: false,
+            min: field.min,
+            max: field.max,
+            variants: field.variants,
+            depth: 0,
+        }
+    }
+
+    fn build_grouped_items(nested_any: &dyn Any, metadata: &[FieldMetadata]) -> Vec<MenuItem> {
+        let mut groups: Vec<&str> = Vec::new();
+        for field in metadata {
+            let group = field.group.unwrap_or("General");
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+
+        // Ungrouped fields form the default section and always come first.
+        if let Some(position) = groups.iter().position(|group| *group == "General") {
+            let general = groups.remove(position);
+            groups.insert(0, general);
+        }
+
+        let mut items = Vec::new();
+        for group in groups {
+            items.push(MenuItem {
+                key: String::new(),
+                label: group.to_string(),
+                value: String::new(),
+                is_submenu: false,
+                is_vec_container: false,
+                is_option: false,
+                field_type: FieldType::Unknown,
+                description: None,
+                render_markup: false,
+                readonly: false,
+                secret: false,
+                multiline: false,
+                is_header: true,
+                min: None,
+                max: None,
+                variants: None,
+                depth: 0,
+            });
+
+            items.extend(
+                metadata
+                    .iter()
+                    .filter(|field| field.group.unwrap_or("General") == group)
+                    .map(|field| Self::build_menu_item(nested_any, field)),
+            );
+        }
+
+        items
+    }
+
+    fn build_vec_items(container: &dyn Any, field: &FieldMetadata) -> Vec<MenuItem> {
+        let len = field
+            .vec_len
+            .as_ref()
+            .and_then(|vec_len| vec_len(container))
+            .unwrap_or(0);
+
+        let keys = if field.is_map {
+            entry_keys(container, field)
+        } else {
+            (0..len).map(|index| format!("[{}]", index)).collect()
         };
+
+        keys.into_iter()
+            .enumerate()
+            .map(|(index, key)| MenuItem {
+                label: if field.element_types.is_some() {
+                    index.to_string()
+                } else {
+                    key.clone()
+                },
+                key,
+                value: Self::item_value(container, field, Some(index)),
+                is_submenu: field.is_nested,
+                is_vec_container: false,
+                is_option: false,
+                field_type: element_type(field, Some(index)),
+                description: None,
+                render_markup: field.render_markup,
+                readonly: field.readonly,
+                secret: field.secret,
+                multiline: field.multiline,
+                is_header: false,
+                min: field.min,
+                max: field.max,
+                variants: field.variants,
+                depth: 0,
+            })
+            .collect()
+    }
+
+    // Updates the value shown for `field_path` on every open level it passes
+    // through, leaving the items, selection and filter as they are.
+    pub fn refresh_value<T: ConfigMenuTrait>(
+        &mut self,
+        config: &T,
+        field_path: &[String],
+    ) -> Result<(), String> {
+        let depth = self.menu_stack.len();
+
+        for (level_index, level) in self.menu_stack.iter_mut().enumerate() {
+            let Some(key) = field_path
+                .strip_prefix(level.field_path.as_slice())
+                .and_then(|rest| rest.first())
+            else {
+                continue;
+            };
+
+            let item_path = &field_path[..=level.field_path.len()];
+            let value = with_field_at_path(
+                config.as_any(),
+                T::field_metadata(),
+                item_path,
+                Self::item_value,
+            )?;
+
+            let mut items = vec![&mut level.items];
+            if level_index + 1 == depth
+                && let Some(filtered_items) = self.filtered_items.as_mut()
+            {
+                items.push(filtered_items);
+            }
+
+            for item in items
+                .into_iter()
+                .flat_map(|items| items.iter_mut())
+                .filter(|item| !item.is_header && item.key == *key)
+            {
+                item.value = value.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_current_level_path(&self) -> Vec<String> {
+        self.menu_stack
+            .last()
+            .map(|level| level.field_path.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn get_current_field_path(&self) -> Vec<String> {
+        let mut path = self.get_current_level_path();
+
+        if let Some(item) = self.get_current_item() {
+            path.push(item.key.clone());
+        }
+
+        path
+    }
+
+    pub fn get_navigation_path(&self) -> Vec<String> {
+        self.menu_stack
+            .iter()
+            .skip(1)
+            .filter_map(|level| level.field_path.last().cloned())
+            .collect()
+    }
+
+    #[deprecated(note = "use `current_items`; the items now live in `menu_stack`")]
+    pub fn items(&self) -> &[MenuItem] {
+        self.current_items()
+    }
+
+    pub fn current_items(&self) -> &[MenuItem] {
+        match &self.filtered_items {
+            Some(items) => items,
+            None => self.menu_stack.last().map_or(&[], |level| &level.items),
+        }
+    }
+
+    pub fn next(&mut self) {
+        let items = self.current_items();
+        if items.is_empty() {
+            return;
+        }
+        let mut i = self.list_state.selected().unwrap_or(items.len() - 1);
+        for _ in 0..items.len() {
+            i = (i + 1) % items.len();
+            if !items[i].is_header {
+                break;
+            }
+        }
+        self.list_state.select(Some(i));
+        self.current_selection = i;
+    }
+
+    pub fn previous(&mut self) {
+        let items = self.current_items();
+        if items.is_empty() {
+            return;
+        }
+        let len = items.len();
+        let mut i = self.list_state.selected().unwrap_or(0);
+        for _ in 0..len {
+            i = (i + len - 1) % len;
+            if !items[i].is_header {
+                break;
+            }
+        }
         self.list_state.select(Some(i));
         self.current_selection = i;
     }
 
+    pub fn select(&mut self, index: usize) {
+        let items = self.current_items();
+        if items.is_empty() {
+            return;
+        }
+        let i = index.min(items.len() - 1);
+        let i = (i..items.len())
+            .chain((0..i).rev())
+            .find(|&i| !items[i].is_header)
+            .unwrap_or(i);
+        self.list_state.select(Some(i));
+        self.current_selection = i;
+    }
+
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+
+    pub fn select_last(&mut self) {
+        self.select(self.current_items().len().saturating_sub(1));
+    }
+
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        let selected_key = self.get_current_item().map(|item| item.key.clone());
+
+        // Match against the whole level, not the previous filtered view.
+        self.filtered_items = None;
+        self.filtered_items = filter.as_ref().map(|query| {
+            let query = query.to_lowercase();
+            self.current_items()
+                .iter()
+                .filter(|item| !item.is_header && item.label.to_lowercase().contains(&query))
+                .cloned()
+                .collect()
+        });
+        self.filter = filter;
+
+        if self.current_items().is_empty() {
+            self.current_selection = 0;
+            self.list_state.select(None);
+            return;
+        }
+
+        // Stay on the same field when it is still visible.
+        let index = selected_key
+            .and_then(|key| self.current_items().iter().position(|item| item.key == key))
+            .unwrap_or(0);
+        self.select(index);
+    }
+
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inner = self.list_area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+
+        let index = *self.row_items.get((row - inner.y) as usize)?;
+        self.current_items()
+            .get(index)
+            .filter(|item| !item.is_header)
+            .map(|_| index)
+    }
+
+    pub fn jump_to_prefix(&mut self, c: char) {
+        let items = self.current_items();
+        let len = items.len();
+        let c = c.to_lowercase().collect::<String>();
+        if let Some(i) = (1..=len)
+            .map(|offset| (self.current_selection + offset) % len)
+            .find(|&i| !items[i].is_header && items[i].label.to_lowercase().starts_with(&c))
+        {
+            self.list_state.select(Some(i));
+            self.current_selection = i;
+        }
+    }
+
+    pub fn page_down(&mut self, page: usize) {
+        self.select(self.current_selection.saturating_add(page.max(1)));
+    }
+
+    pub fn page_up(&mut self, page: usize) {
+        let target = self.current_selection.saturating_sub(page.max(1));
+        // Moving up onto a header should land on the field above it, not
+        // back on the one below.
+        match (0..=target).rev().find(|&i| {
+            self.current_items()
+                .get(i)
+                .is_some_and(|item| !item.is_header)
+        }) {
+            Some(i) => self.select(i),
+            None => self.select(target),
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.page_down(1);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.page_up(1);
+    }
+
     pub fn get_current_item(&self) -> Option<&MenuItem> {
-        self.items.get(self.current_selection)
+        self.current_items().get(self.current_selection)
     }
 
     pub fn can_go_back(&self) -> bool {
@@ -769,12 +4571,357 @@ impl MenuState {
             self.breadcrumb.pop();
 
             if let Some(prev_level) = self.menu_stack.last() {
-                self.items = prev_level.items.clone();
-                self.current_selection = prev_level.selection;
-                self.list_state.select(Some(self.current_selection));
+                let selection = prev_level.selection;
+                self.filter = None;
+                self.filtered_items = None;
+                self.select(selection);
+            }
+        }
+    }
+}
+
+fn render_menu_item(
+    item: &MenuItem,
+    prefix: &str,
+    hide_values: bool,
+    theme: &MenuTheme,
+    width: usize,
+    layout: &MenuLayout,
+    label_width: Option<usize>,
+) -> ListItem<'static> {
+    if item.is_header {
+        let style = Style::default()
+            .fg(theme.header_fg)
+            .add_modifier(Modifier::BOLD);
+        let spans = vec![Span::styled(format!("{}{}", prefix, item.label), style)];
+        return ListItem::new(Line::from(truncate_spans(spans, width)));
+    }
+
+    let style = if item.readonly {
+        Style::default().fg(theme.readonly_fg)
+    } else {
+        Style::default().fg(theme.item_fg)
+    };
+    let indicator = item_indicator(item, theme);
+
+    let label_with_colon = match label_width {
+        Some(label_width) => format!(
+            "{}: {}",
+            item.label,
+            " ".repeat(label_width.saturating_sub(item.label.width()))
+        ),
+        None => format!("{}: ", item.label),
+    };
+
+    let (label, value) = if hide_values {
+        (item.label.clone(), Vec::new())
+    } else if item.secret {
+        (label_with_colon, vec![Span::styled(SECRET_MASK, style)])
+    } else if item.field_type == FieldType::Bool
+        && !item.is_vec_container
+        && matches!(item.value.as_str(), "true" | "false")
+    {
+        let checkbox = if item.value == "true" {
+            &theme.checkbox_checked
+        } else {
+            &theme.checkbox_unchecked
+        };
+        (
+            label_with_colon,
+            vec![Span::styled(checkbox.clone(), style)],
+        )
+    } else if let Some((_, active)) = enum_segments(
+        item,
+        layout,
+        width.saturating_sub(prefix.width() + label_with_colon.width() + indicator.width()),
+    ) {
+        let mut spans = vec![Span::styled("[ ", style)];
+        for (index, variant) in item.variants.unwrap_or_default().iter().enumerate() {
+            if index > 0 {
+                spans.push(Span::styled(" | ", style));
+            }
+            let variant_style = if index == active {
+                style.add_modifier(Modifier::REVERSED)
+            } else {
+                style
+            };
+            spans.push(Span::styled(*variant, variant_style));
+        }
+        spans.push(Span::styled(" ]", style));
+        (label_with_colon, spans)
+    } else {
+        let markup = if item.render_markup {
+            let raw = if item.field_type == FieldType::String {
+                strip_debug_quotes(&item.value)
+            } else {
+                item.value.clone()
+            };
+            parse_markup(&raw, style)
+        } else {
+            None
+        };
+
+        (
+            label_with_colon,
+            markup.unwrap_or_else(|| vec![Span::styled(item.value.clone(), style)]),
+        )
+    };
+
+    let label = format!("{}{}", prefix, label);
+    let fixed = label.width() + indicator.width();
+    let label = Span::styled(label, style);
+    let indicator = Span::styled(indicator.to_string(), style);
+
+    match layout.value_overflow {
+        // Shorten the value rather than the whole line so the label and the
+        // submenu indicator stay visible.
+        ValueOverflow::Truncate if fixed < width => {
+            let mut spans = vec![label];
+            spans.extend(truncate_spans(value, width - fixed));
+            spans.push(indicator);
+            ListItem::new(Line::from(spans))
+        }
+        ValueOverflow::Truncate => {
+            let mut spans = vec![label];
+            spans.extend(value);
+            spans.push(indicator);
+            ListItem::new(Line::from(truncate_spans(spans, width)))
+        }
+        ValueOverflow::Wrap => {
+            let mut spans = vec![label];
+            spans.extend(value);
+            spans.push(indicator);
+            ListItem::new(wrap_spans(spans, width))
+        }
+    }
+}
+
+// Indentation for an item `depth` levels down, ending in a connector when the
+// theme has them. Top-level items get none.
+fn depth_prefix(item: &MenuItem, is_last: bool, theme: &MenuTheme) -> String {
+    let Some(levels) = item.depth.checked_sub(1) else {
+        return String::new();
+    };
+
+    match theme.tree_connectors {
+        Some(connectors) => {
+            let connector = if is_last {
+                connectors.last
+            } else {
+                connectors.branch
+            };
+            format!("{}{}", " ".repeat(theme.indent_width * levels), connector)
+        }
+        None => " ".repeat(theme.indent_width * item.depth),
+    }
+}
+
+fn item_indicator<'a>(item: &MenuItem, theme: &'a MenuTheme) -> &'a str {
+    if item.is_submenu {
+        theme.submenu_indicator.as_str()
+    } else if item.is_vec_container {
+        theme.vec_indicator.as_str()
+    } else {
+        ""
+    }
+}
+
+// The level's widest label, when values are lined up after it.
+fn aligned_label_width(items: &[MenuItem], layout: &MenuLayout) -> Option<usize> {
+    layout.align_values.then(|| {
+        items
+            .iter()
+            .filter(|item| !item.is_header)
+            .map(|item| item.label.width())
+            .max()
+            .unwrap_or(0)
+    })
+}
+
+// Where each variant of an enum row drawn as `[ A | B | C ]` starts, counted
+// in columns from the start of the value, and which variant is active. None
+// means the row shows just the current variant: segmented enums are off, the
+// value isn't a known variant, or the segments don't fit in `room` columns.
+fn enum_segments(item: &MenuItem, layout: &MenuLayout, room: usize) -> Option<(Vec<usize>, usize)> {
+    if !layout.segmented_enums || item.field_type != FieldType::Enum || item.is_vec_container {
+        return None;
+    }
+    let variants = item.variants?;
+    let active = variants.iter().position(|variant| *variant == item.value)?;
+
+    let mut offsets = Vec::new();
+    let mut column = "[ ".len();
+    for variant in variants {
+        offsets.push(column);
+        column += variant.width() + " | ".len();
+    }
+    let total = column - " | ".len() + " ]".len();
+    (total <= room).then_some((offsets, active))
+}
+
+// The longest prefix of `text` that fits in `width` terminal columns, cut at
+// grapheme boundaries so wide characters and emoji are never split.
+fn take_width(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return &text[..offset];
+        }
+    }
+    text
+}
+
+fn truncate_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    let total: usize = spans.iter().map(|span| span.content.width()).sum();
+    if total <= width {
+        return spans;
+    }
+
+    let mut remaining = width.saturating_sub(1);
+    let mut truncated = Vec::new();
+    let mut last_style = Style::default();
+
+    for span in spans {
+        last_style = span.style;
+        let len = span.content.width();
+        if len <= remaining {
+            remaining -= len;
+            truncated.push(span);
+            continue;
+        }
+
+        let cut = take_width(&span.content, remaining).to_string();
+        truncated.push(Span::styled(cut, span.style));
+        break;
+    }
+
+    if width > 0 {
+        truncated.push(Span::styled("…", last_style));
+    }
+    truncated
+}
+
+fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
+    if width == 0 {
+        return vec![Line::from(spans)];
+    }
+
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut line_len = 0;
+
+    for span in spans {
+        let mut rest: &str = &span.content;
+        while !rest.is_empty() {
+            let mut piece = take_width(rest, width - line_len);
+            if piece.is_empty() {
+                if line_len > 0 {
+                    lines.push(Line::from(std::mem::take(&mut line)));
+                    line_len = 0;
+                    continue;
+                }
+                // A grapheme wider than the whole line still has to go somewhere.
+                piece = rest.graphemes(true).next().unwrap_or(rest);
+            }
+
+            line_len += piece.width();
+            line.push(Span::styled(piece.to_string(), span.style));
+            rest = &rest[piece.len()..];
+
+            if line_len >= width {
+                lines.push(Line::from(std::mem::take(&mut line)));
+                line_len = 0;
             }
         }
     }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(Line::from(line));
+    }
+    lines
+}
+
+fn markup_tag_style(tag: &str, style: Style) -> Option<Style> {
+    match tag {
+        "b" => Some(style.add_modifier(Modifier::BOLD)),
+        "i" => Some(style.add_modifier(Modifier::ITALIC)),
+        "u" => Some(style.add_modifier(Modifier::UNDERLINED)),
+        "d" => Some(style.add_modifier(Modifier::DIM)),
+        color => color.parse::<Color>().ok().map(|c| style.fg(c)),
+    }
+}
+
+fn parse_markup(text: &str, base: Style) -> Option<Vec<Span<'static>>> {
+    let mut spans = Vec::new();
+    let mut stack: Vec<(&str, Style)> = vec![("", base)];
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        let style = stack.last()?.1;
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), style));
+        }
+
+        let end = start + rest[start..].find('>')?;
+        let tag = &rest[start + 1..end];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let (open, _) = stack.pop()?;
+            if stack.is_empty() || !(name.is_empty() || name == open) {
+                return None;
+            }
+        } else {
+            stack.push((tag, markup_tag_style(tag, style)?));
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    if stack.len() != 1 {
+        return None;
+    }
+
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), base));
+    }
+
+    Some(spans)
+}
+
+fn render_history_panel<T: ConfigMenuTrait>(
+    frame: &mut Frame,
+    controller: &MenuController<T>,
+    area: Rect,
+    theme: &MenuTheme,
+) {
+    let visible = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = controller
+        .recent_edits(visible)
+        .into_iter()
+        .map(|edit| {
+            if controller.is_secret_path(edit.field_path()) {
+                return Line::from(format!(
+                    "{}: {} -> {}",
+                    edit.field_path().join("."),
+                    SECRET_MASK,
+                    SECRET_MASK
+                ));
+            }
+
+            Line::from(format!(
+                "{}: {} -> {}",
+                edit.field_path().join("."),
+                controller.masked_value_at_path(edit.field_path(), edit.old_value()),
+                controller.masked_value_at_path(edit.field_path(), edit.new_value())
+            ))
+        })
+        .collect();
+
+    let history_widget = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("History"))
+        .style(Style::default().fg(theme.help_fg));
+    frame.render_widget(history_widget, area);
 }
 
 pub fn render_menu<T: ConfigMenuTrait>(
@@ -782,93 +4929,590 @@ pub fn render_menu<T: ConfigMenuTrait>(
     controller: &mut MenuController<T>,
     area: Rect,
 ) {
+    let theme = controller.theme.clone().unwrap_or_default();
+    let layout = controller.layout.clone();
+    let pane_height = |shown: bool, height: u16| if shown { height } else { 0 };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(pane_height(
+                layout.show_breadcrumb,
+                layout.breadcrumb_height,
+            )),
             Constraint::Min(0),
-            Constraint::Length(3),
-            Constraint::Length(3),
+            Constraint::Length(pane_height(layout.show_status, layout.status_height)),
+            Constraint::Length(pane_height(layout.show_help, layout.help_height)),
         ])
         .split(area);
 
     let breadcrumb = controller.menu_state.breadcrumb.join(" > ");
     let breadcrumb_widget = Paragraph::new(breadcrumb)
-        .block(Block::default().borders(Borders::ALL).title("Navigation"))
-        .style(Style::default().fg(Color::Cyan));
-    frame.render_widget(breadcrumb_widget, chunks[0]);
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(if controller.is_dirty() {
+                    "Navigation *"
+                } else {
+                    "Navigation"
+                }),
+        )
+        .style(Style::default().fg(theme.breadcrumb_fg));
+    if layout.show_breadcrumb {
+        frame.render_widget(breadcrumb_widget, chunks[0]);
+    }
+
+    let current_item = controller
+        .menu_state
+        .get_current_item()
+        .filter(|_| layout.show_details);
+    let body_area = if let Some(item) = current_item {
+        // Reserve a help line for the whole level so the pane doesn't jump in
+        // height while moving between fields with and without help text.
+        let has_help = controller
+            .menu_state
+            .current_items()
+            .iter()
+            .any(|item| item.description.is_some());
+
+        let has_slider = controller
+            .menu_state
+            .current_items()
+            .iter()
+            .any(|item| item.slider_ratio().is_some());
+
+        let mut lines = vec![Line::from(format!("Type: {}", item.type_label()))];
+        if has_help {
+            lines.push(Line::from(item.description.unwrap_or_default()));
+        }
+        let text_height = lines.len() as u16;
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(text_height + has_slider as u16 + 2),
+            ])
+            .split(chunks[1]);
+
+        let details_block = Block::default().borders(Borders::ALL).title("Details");
+        let details_inner = details_block.inner(rows[1]);
+        let details_widget = Paragraph::new(lines)
+            .block(details_block)
+            .style(Style::default().fg(theme.help_fg));
+        frame.render_widget(details_widget, rows[1]);
+
+        // The gauge's position gives a masked value away as much as its label.
+        if let Some(ratio) = item.slider_ratio()
+            && !item.secret
+            && !controller.hide_values
+            && details_inner.height > text_height
+        {
+            let slider = LineGauge::default()
+                .filled_style(Style::default().fg(theme.highlight_fg))
+                .unfilled_style(Style::default().fg(theme.help_fg))
+                .label(format!(
+                    "{} [{}..{}]",
+                    item.value,
+                    item.min.unwrap_or_default(),
+                    item.max.unwrap_or_default()
+                ))
+                .ratio(ratio);
+            let slider_area = Rect {
+                y: details_inner.y + text_height,
+                height: 1,
+                ..details_inner
+            };
+            frame.render_widget(slider, slider_area);
+        }
+
+        rows[0]
+    } else {
+        chunks[1]
+    };
+
+    let list_area = if controller.show_history {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(body_area);
+        render_history_panel(frame, controller, columns[1], &theme);
+        columns[0]
+    } else {
+        body_area
+    };
 
+    let item_width = (list_area.width as usize).saturating_sub(2 + theme.highlight_symbol.width());
+    let label_width = aligned_label_width(controller.menu_state.current_items(), &layout);
+    let item_count = controller.menu_state.current_items().len();
     let items: Vec<ListItem> = controller
         .menu_state
-        .items
+        .current_items()
         .iter()
-        .map(|item| {
-            let indicator = if item.is_submenu {
-                " >"
-            } else if item.is_vec_container {
-                " []"
-            } else {
-                ""
-            };
-            let content = format!("{}: {}{}", item.label, item.value, indicator);
-            ListItem::new(Line::from(vec![Span::styled(
-                content,
-                Style::default().fg(Color::White),
-            )]))
+        .enumerate()
+        .map(|(index, item)| {
+            render_menu_item(
+                item,
+                &depth_prefix(item, index + 1 == item_count, &theme),
+                controller.hide_values,
+                &theme,
+                item_width,
+                &layout,
+                label_width,
+            )
         })
         .collect();
 
+    let fields: Vec<usize> = (0..controller.menu_state.current_items().len())
+        .filter(|&i| !controller.menu_state.current_items()[i].is_header)
+        .collect();
+    let position = fields
+        .iter()
+        .position(|&i| i == controller.menu_state.current_selection);
+    let settings_title = match position {
+        Some(position) => format!("Settings ({}/{})", position + 1, fields.len()),
+        None => "Settings".to_string(),
+    };
+
+    let item_heights: Vec<usize> = items.iter().map(ListItem::height).collect();
     let items_widget = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Settings"))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol(">> ");
+        .block(Block::default().borders(Borders::ALL).title(settings_title))
+        .highlight_style(theme.highlight_style())
+        .highlight_symbol(theme.highlight_symbol.as_str());
 
     frame.render_stateful_widget(
         items_widget,
-        chunks[1],
+        list_area,
         &mut controller.menu_state.list_state,
     );
 
-    let status_text = if controller.editing_mode {
+    let offset = controller.menu_state.list_state.offset();
+    controller.menu_state.list_area = list_area;
+    controller.menu_state.row_items = item_heights
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .flat_map(|(index, &height)| std::iter::repeat_n(index, height))
+        .collect();
+
+    controller.menu_state.visible_rows = list_area.height.saturating_sub(2) as usize;
+    let item_count = controller.menu_state.current_items().len();
+    if item_count > controller.menu_state.visible_rows {
+        let mut scrollbar_state =
+            ScrollbarState::new(item_count).position(controller.menu_state.current_selection);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            list_area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+    }
+
+    let current_secret = controller
+        .menu_state
+        .get_current_item()
+        .is_some_and(|item| item.secret);
+    let editing_multiline = controller.editing_mode && controller.is_current_multiline();
+
+    let status_text = if editing_multiline {
+        let (row, column) = controller.edit_cursor_position();
+        format!("Editing: line {}, column {}", row + 1, column + 1)
+    } else if controller.editing_mode && current_secret {
+        format!(
+            "Editing: {}",
+            "•".repeat(controller.edit_buffer.graphemes(true).count())
+        )
+    } else if controller.editing_mode {
         format!("Editing: {}", controller.edit_buffer)
+    } else if let Some(key) = &controller.new_map_key {
+        format!("New key: {}", key)
+    } else if controller.search_mode {
+        format!(
+            "Search: {}",
+            controller.menu_state.filter.as_deref().unwrap_or("")
+        )
+    } else if let Some(message) = &controller.status_message {
+        message.clone()
+    } else if let Some(query) = &controller.menu_state.filter {
+        format!("Filter: {} (Esc to clear)", query)
+    } else if controller.hide_values
+        && let Some(item) = controller.menu_state.get_current_item()
+    {
+        let value = if item.secret {
+            SECRET_MASK
+        } else {
+            &item.value
+        };
+        format!("{}: {}", item.label, value)
     } else {
         "Ready".to_string()
     };
 
     let status_widget = Paragraph::new(status_text)
         .block(Block::default().borders(Borders::ALL).title("Status"))
-        .style(if controller.editing_mode {
-            Style::default().fg(Color::Green)
-        } else {
-            Style::default().fg(Color::Gray)
-        });
-    frame.render_widget(status_widget, chunks[2]);
+        .style(
+            if controller.editing_mode || controller.search_mode || controller.new_map_key.is_some()
+            {
+                Style::default().fg(theme.status_editing_fg)
+            } else {
+                Style::default().fg(theme.status_fg)
+            },
+        );
+    if layout.show_status {
+        frame.render_widget(status_widget, chunks[2]);
+    }
 
-    if controller.editing_mode {
-        frame.set_cursor_position((
-            chunks[2].x + controller.edit_cursor as u16 + 10,
-            chunks[2].y + 1,
-        ));
+    if controller.editing_mode && layout.show_status && !editing_multiline {
+        let cursor_column = if current_secret {
+            controller.edit_cursor
+        } else {
+            controller.edit_buffer[..controller.cursor_byte_offset()].width()
+        };
+        frame.set_cursor_position((chunks[2].x + cursor_column as u16 + 10, chunks[2].y + 1));
     }
 
-    let help_text = if controller.editing_mode {
-        "Esc: Cancel | Enter: Save | Left/Right: Move cursor | Backspace/Del: Delete"
+    let keys = controller.keybindings();
+    let save = format!("{}: Save", keys.save);
+    let reload = format!("{}: Reload", keys.reload);
+    let quit = format!("{}: Quit", keys.quit);
+    let mut help_text = if controller.popup.is_some() {
+        "Up/Down: Choose | Enter: Select | Esc: Cancel".to_string()
+    } else if editing_multiline {
+        "Enter: New line | Alt+Enter: Save | Esc: Cancel | Arrows: Move cursor".to_string()
+    } else if controller.new_map_key.is_some() {
+        "Type a key | Enter: Add | Esc: Cancel | Backspace: Delete".to_string()
+    } else if controller.search_mode {
+        "Type to filter | Up/Down: Navigate | Enter: Keep filter | Esc: Clear | Backspace: Delete"
+            .to_string()
+    } else if controller.editing_mode {
+        "Esc: Cancel | Enter: Save | Left/Right: Move cursor | Ctrl+Left/Right: Word | Home/End: Start/End | Backspace/Del: Delete | Ctrl+W: Delete word".to_string()
+    } else if controller.is_current_readonly() && !controller.is_current_submenu() {
+        format!("Read-only | Up/Down: Navigate | Esc: Back | {save} | {reload} | {quit}")
     } else if controller.is_current_submenu() {
-        "Up/Down: Navigate | Enter: Open submenu | Esc: Back | s: Save | q: Quit"
+        format!("Up/Down: Navigate | Enter: Open submenu | Esc: Back | {save} | {quit}")
     } else if controller.is_current_boolean() {
-        "Up/Down: Navigate | Enter: Toggle | Esc: Back | s: Save | r: Reload | q: Quit"
+        format!("Up/Down: Navigate | Enter/Space: Toggle | Esc: Back | {save} | {reload} | {quit}")
+    } else if controller.is_current_enum() {
+        format!("Left/Right: Change | Up/Down: Navigate | Esc: Back | {save} | {reload} | {quit}")
+    } else if controller.is_current_numeric() {
+        format!(
+            "Left/Right: Adjust | Enter: Edit | Up/Down: Navigate | Esc: Back | {save} | {quit}"
+        )
     } else if controller.menu_state.can_go_back() {
-        "Up/Down: Navigate | Enter: Edit | Esc: Back | s: Save | r: Reload | q: Quit"
+        format!("Up/Down: Navigate | Enter: Edit | Esc: Back | {save} | {reload} | {quit}")
     } else {
-        "Up/Down: Navigate | Enter: Edit | s: Save | r: Reload | q: Quit"
+        format!("Up/Down: Navigate | Enter: Edit | {save} | {reload} | {quit}")
     };
 
+    let browsing =
+        !controller.editing_mode && !controller.search_mode && controller.new_map_key.is_none();
+    if browsing && controller.is_current_option() && !controller.is_current_readonly() {
+        help_text.push_str(&format!(" | {}: Set/Unset", keys.toggle_option));
+    }
+    if browsing && controller.is_current_resettable() {
+        help_text.push_str(&format!(" | {}: Reset", keys.reset));
+    }
+    if browsing && !controller.is_current_readonly() {
+        if controller.can_resize_elements() {
+            help_text.push_str(&format!(" | {}: Add | {}: Delete", keys.add, keys.delete));
+        }
+        if controller.can_move_elements() {
+            help_text.push_str(" | Shift+Up/Down: Move");
+        }
+    }
+    if browsing && controller.popup.is_none() && item_count > controller.menu_state.visible_rows {
+        help_text.push_str(" | PgUp/PgDn: Page | Home/End: First/Last");
+    }
+    if browsing && controller.popup.is_none() {
+        help_text.push_str(&format!(" | {}: Search", keys.search));
+    }
+    if browsing && !controller.history.is_empty() {
+        help_text.push_str(&format!(
+            " | {}: Undo | Ctrl+{}: Redo | {}: History",
+            keys.undo, keys.redo, keys.history
+        ));
+    }
+
     let help_widget = Paragraph::new(help_text)
         .block(Block::default().borders(Borders::ALL).title("Help"))
-        .style(Style::default().fg(Color::Gray));
-    frame.render_widget(help_widget, chunks[3]);
+        .style(Style::default().fg(theme.help_fg));
+    if layout.show_help {
+        frame.render_widget(help_widget, chunks[3]);
+    }
+
+    if editing_multiline {
+        render_multiline_editor(frame, controller, chunks[1], &theme, current_secret);
+    }
+
+    if let Some(popup) = controller.popup.as_mut() {
+        render_popup(frame, popup, area, &theme);
+    }
+}
+
+fn render_multiline_editor<T: ConfigMenuTrait>(
+    frame: &mut Frame,
+    controller: &MenuController<T>,
+    area: Rect,
+    theme: &MenuTheme,
+    secret: bool,
+) {
+    let label = controller
+        .menu_state
+        .get_current_item()
+        .map(|item| item.label.clone())
+        .unwrap_or_default();
+    let (row, mut column) = controller.edit_cursor_position();
+
+    let text = if secret {
+        let before = &controller.edit_buffer[..controller.cursor_byte_offset()];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        column = before[line_start..].graphemes(true).count();
+        controller
+            .edit_buffer
+            .graphemes(true)
+            .map(|grapheme| if grapheme == "\n" { "\n" } else { "•" })
+            .collect()
+    } else {
+        controller.edit_buffer.clone()
+    };
+
+    // Keep the cursor's row in view when the value is taller than the area.
+    let visible_rows = area.height.saturating_sub(2) as usize;
+    let scroll = (row + 1).saturating_sub(visible_rows);
+
+    let editor = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Editing {}", label)),
+        )
+        .style(Style::default().fg(theme.status_editing_fg))
+        .scroll((scroll as u16, 0));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(editor, area);
+    frame.set_cursor_position((
+        area.x + 1 + (column as u16).min(area.width.saturating_sub(3)),
+        area.y + 1 + (row - scroll) as u16,
+    ));
+}
+
+fn render_popup(frame: &mut Frame, popup: &mut PopupState, area: Rect, theme: &MenuTheme) {
+    let content_width = popup
+        .options
+        .iter()
+        .map(|option| option.width())
+        .chain(std::iter::once(popup.title.width()))
+        .max()
+        .unwrap_or(0);
+
+    let width = (content_width as u16 + 7).min(area.width);
+    let height = (popup.options.len() as u16 + 2).min(area.height);
+    let popup_area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let items: Vec<ListItem> = popup
+        .options
+        .iter()
+        .map(|option| ListItem::new(option.clone()))
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(popup.title.clone()),
+        )
+        .highlight_style(theme.highlight_style())
+        .highlight_symbol(theme.highlight_symbol.as_str());
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_stateful_widget(list, popup_area, &mut popup.list_state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+    struct Server {
+        host: String,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+    struct Settings {
+        name: String,
+        retries: u32,
+        servers: Vec<Server>,
+    }
+
+    fn settings_with_server() -> MenuController<Settings> {
+        MenuController::new(Settings {
+            servers: vec![Server::default()],
+            ..Settings::default()
+        })
+    }
+
+    #[test]
+    fn derive_resolves_inside_the_crate() {
+        let controller = MenuController::new(Settings::default());
+
+        assert_eq!(controller.menu_state.current_items().len(), 3);
+    }
+
+    #[test]
+    fn field_metadata_is_built_once_per_process() {
+        let first = Settings::field_metadata().as_ptr() as usize;
+        let other_thread = std::thread::spawn(|| Settings::field_metadata().as_ptr() as usize)
+            .join()
+            .unwrap();
+
+        assert_eq!(first, other_thread);
+        assert_eq!(first, Settings::field_metadata().as_ptr() as usize);
+    }
+
+    #[test]
+    fn strict_parsing_applies_to_every_entry_point() {
+        let mut controller = settings_with_server();
+        controller.strict_numeric_parsing = true;
+
+        assert!(
+            controller
+                .parse_value_at_path(&["retries"], "1_000")
+                .is_err()
+        );
+        assert!(controller.set_field(&["retries"], "1_000").is_err());
+        assert!(controller.parse_value_at_path(&["retries"], "1000").is_ok());
+    }
+
+    #[cfg(feature = "group_digits")]
+    #[test]
+    fn commas_group_digits() {
+        assert_eq!(strip_digit_separators("1,000").unwrap(), "1000");
+        assert!(strip_digit_separators("1,00,0").is_err());
+    }
+
+    #[cfg(not(feature = "group_digits"))]
+    #[test]
+    fn commas_need_group_digits() {
+        assert!(u32::parse_from_string("1,000".to_string()).is_err());
+    }
+
+    fn span_text(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn truncate_spans_counts_terminal_columns() {
+        let spans = vec![Span::raw("日本語テキスト")];
+
+        assert_eq!(span_text(&truncate_spans(spans, 7)), "日本語…");
+    }
+
+    #[test]
+    fn wrap_spans_counts_terminal_columns() {
+        let lines = wrap_spans(vec![Span::raw("日本語")], 4);
+        let lines: Vec<String> = lines.iter().map(|line| span_text(&line.spans)).collect();
+
+        assert_eq!(lines, ["日本", "語"]);
+    }
+
+    #[test]
+    fn rebuild_stops_at_the_container_of_a_removed_element() {
+        let mut controller = settings_with_server();
+        controller
+            .menu_state
+            .enter_submenu_by_name(&controller.config, "servers")
+            .unwrap();
+        controller
+            .menu_state
+            .enter_submenu_by_name(&controller.config, "[0]")
+            .unwrap();
+
+        controller.config.servers.clear();
+        controller.rebuild_menu_state().unwrap();
+
+        assert_eq!(controller.menu_state.get_current_level_path(), ["servers"]);
+    }
+
+    #[test]
+    fn failed_rebuild_leaves_the_menu_untouched() {
+        let mut controller = settings_with_server();
+        controller.menu_state.select(1);
+        controller.menu_state.breadcrumb.push("name".to_string());
+        controller.menu_state.menu_stack.push(MenuLevel {
+            items: Vec::new(),
+            selection: 0,
+            title: "name".to_string(),
+            field_path: vec!["name".to_string()],
+        });
+
+        assert!(controller.rebuild_menu_state().is_err());
+        assert_eq!(controller.menu_state.breadcrumb, ["Settings", "name"]);
+        assert_eq!(controller.menu_state.get_current_level_path(), ["name"]);
+        assert_eq!(controller.menu_state.current_selection, 1);
+    }
+
+    #[test]
+    fn one_line_summary_lists_top_level_fields() {
+        let mut controller = settings_with_server();
+        controller.set_field(&["name"], "api").unwrap();
+        controller.set_field(&["retries"], "3").unwrap();
+
+        assert_eq!(controller.one_line_summary(80), "name=api retries=3");
+        assert_eq!(controller.one_line_summary(10), "name=api …");
+    }
+
+    #[test]
+    fn field_types_map_from_type_names() {
+        assert_eq!(FieldType::from_str("u32"), FieldType::U32);
+        assert_eq!(FieldType::from_str("String"), FieldType::String);
+        assert_eq!(FieldType::from_str("bool"), FieldType::Bool);
+        assert_eq!(FieldType::from_str("Widget"), FieldType::Unknown);
+
+        for field_type in [
+            FieldType::Char,
+            FieldType::Path,
+            FieldType::Addr,
+            FieldType::Duration,
+            FieldType::Enum,
+            FieldType::I128,
+            FieldType::F64,
+        ] {
+            assert_eq!(FieldType::from_str(&field_type.to_string()), field_type);
+        }
+    }
+
+    #[test]
+    fn scalar_edits_refresh_the_item_in_place() {
+        let mut controller = settings_with_server();
+        controller
+            .menu_state
+            .enter_submenu_by_name(&controller.config, "servers")
+            .unwrap();
+        controller
+            .menu_state
+            .enter_submenu_by_name(&controller.config, "[0]")
+            .unwrap();
+        controller.menu_state.menu_stack[0].selection = 2;
+
+        controller
+            .set_field(&["servers", "[0]", "host"], "db")
+            .unwrap();
+        controller.start_editing();
+        controller.handle_edit_input('2');
+        controller.finish_editing().unwrap();
+
+        assert_eq!(controller.config.servers[0].host, "db2");
+        assert_eq!(controller.menu_state.current_items()[0].value, "\"db2\"");
+        assert_eq!(controller.menu_state.current_selection, 0);
+        assert_eq!(controller.menu_state.menu_stack.len(), 3);
+        assert_eq!(controller.menu_state.menu_stack[0].selection, 2);
+    }
 }
@@ -0,0 +1,290 @@
+use {
+    ratatui_cfg::{ConfigMenu, ConfigMenuTrait, FieldType, MenuController, field_path_id},
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Tls {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    retries: u32,
+    tls: Tls,
+}
+
+#[test]
+fn derived_struct_builds_a_menu() {
+    let controller = MenuController::new(Config::default());
+    let keys: Vec<_> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.key.clone())
+        .collect();
+
+    assert_eq!(keys, ["name", "retries", "tls"]);
+}
+
+#[test]
+fn derived_metadata_describes_fields() {
+    let metadata = Config::field_metadata();
+
+    assert_eq!(metadata[1].field_type, FieldType::U32);
+    assert!(metadata[2].is_nested);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigMenu)]
+struct NoDefault {
+    label: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Lists {
+    counts: Vec<u32>,
+    entries: Vec<NoDefault>,
+    extra: Option<NoDefault>,
+    by_name: HashMap<String, NoDefault>,
+}
+
+#[test]
+fn elements_without_default_cannot_be_created() {
+    let metadata = Lists::field_metadata();
+
+    assert!(metadata[0].element_pusher.is_some());
+    assert!(metadata[1].element_pusher.is_none());
+    assert!(metadata[2].option_default_setter.is_none());
+    assert!(metadata[3].entry_inserter.is_none());
+}
+
+#[test]
+fn elements_with_default_are_appended() {
+    let mut controller = MenuController::new(Lists::default());
+    controller
+        .menu_state
+        .enter_submenu_by_name(&controller.config, "counts")
+        .unwrap();
+
+    controller.push_vec_element().unwrap();
+    controller.push_vec_element().unwrap();
+
+    assert_eq!(controller.config.counts, [0, 0]);
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+#[serde(tag = "kind")]
+enum Backend {
+    #[default]
+    Memory,
+    Disk,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+#[serde(rename_all = "lowercase")]
+enum LogLevel {
+    #[default]
+    Info,
+    Debug,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+struct Storage {
+    backend: Backend,
+    level: LogLevel,
+}
+
+#[test]
+fn tagged_enum_edits_round_trip() {
+    let mut controller = MenuController::new(Storage::default());
+
+    controller.set_field(&["backend"], "Disk").unwrap();
+    assert_eq!(controller.get_field(&["backend"]).as_deref(), Some("Disk"));
+    assert!(
+        controller
+            .set_field(&["backend", "kind"], "Memory")
+            .is_err()
+    );
+
+    let saved = toml::to_string(&controller.config).unwrap();
+    let table: toml::Table = toml::from_str(&saved).unwrap();
+    assert_eq!(table["backend"]["kind"].as_str(), Some("Disk"));
+    let loaded: Storage = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded, controller.config);
+    assert_eq!(loaded.backend, Backend::Disk);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.backend, Backend::Memory);
+}
+
+#[test]
+fn renamed_variants_accept_their_serialized_name() {
+    let mut controller = MenuController::new(Storage::default());
+
+    controller.set_field(&["level"], "debug").unwrap();
+    assert_eq!(controller.config.level, LogLevel::Debug);
+    controller.set_field(&["level"], "Info").unwrap();
+    assert_eq!(controller.config.level, LogLevel::Info);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Ordered {
+    #[config_menu(order = 2)]
+    name: String,
+    #[config_menu(order = 0)]
+    retries: u32,
+    #[config_menu(order = 1)]
+    tls: Tls,
+}
+
+#[test]
+fn patches_keyed_by_field_id_survive_reordering() {
+    let names: Vec<_> = Ordered::field_metadata().iter().map(|m| m.name).collect();
+    assert_eq!(names, ["retries", "tls", "name"]);
+    assert_eq!(Ordered::field_id("tls"), Config::field_id("tls"));
+    assert_eq!(Ordered::field_id("tls"), Some(field_path_id(&["tls"])));
+    assert_ne!(field_path_id(&["tls", "port"]), field_path_id(&["port"]));
+
+    let mut edited = MenuController::new(Config::default());
+    edited.set_field(&["retries"], "3").unwrap();
+    edited.set_field(&["tls", "port"], "8443").unwrap();
+    let patch = edited.diff();
+    assert_eq!(patch.len(), 2);
+
+    let mut reordered = MenuController::new(Ordered::default());
+    reordered.apply_patch(&patch).unwrap();
+    assert_eq!(reordered.config.retries, 3);
+    assert_eq!(reordered.config.tls.port, 8443);
+    assert_eq!(reordered.count_dirty_fields(), 2);
+
+    let unknown = HashMap::from([(field_path_id(&["missing"]), 1.into())]);
+    assert!(reordered.apply_patch(&unknown).is_err());
+}
+
+type Port = u16;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Aliased {
+    #[config_menu(as = "u16")]
+    port: Port,
+}
+
+#[test]
+fn aliased_primitives_are_edited_as_their_type() {
+    let mut controller = MenuController::new(Aliased::default());
+
+    assert_eq!(Aliased::field_metadata()[0].field_type, FieldType::U16);
+    controller.set_field(&["port"], "8080").unwrap();
+    assert_eq!(controller.config.port, 8080);
+    assert!(controller.set_field(&["port"], "port").is_err());
+    assert_eq!(controller.config.port, 8080);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct WithCache {
+    name: String,
+    #[config_menu(skip)]
+    cache: Vec<u8>,
+}
+
+#[test]
+fn skipped_fields_are_left_out_of_the_menu() {
+    let config = WithCache {
+        name: "api".to_string(),
+        cache: vec![1, 2],
+    };
+
+    assert_eq!(WithCache::get_field_metadata().len(), 1);
+    let saved = toml::to_string(&config).unwrap();
+    let loaded: WithCache = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.cache, [1, 2]);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Labelled {
+    #[config_menu(rename = "Max Connection Retries")]
+    max_conn_retries: u32,
+}
+
+#[test]
+fn renamed_fields_keep_their_path_key() {
+    let mut controller = MenuController::new(Labelled::default());
+    let item = &controller.menu_state.current_items()[0];
+
+    assert_eq!(item.label, "Max Connection Retries");
+    assert_eq!(item.key, "max_conn_retries");
+    controller.set_field(&["max_conn_retries"], "4").unwrap();
+    assert_eq!(controller.config.max_conn_retries, 4);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Documented {
+    #[config_menu(help = "Seconds to wait before giving up")]
+    timeout: u32,
+    retries: u32,
+}
+
+#[test]
+fn help_text_is_carried_in_the_metadata() {
+    let metadata = Documented::field_metadata();
+
+    assert_eq!(
+        metadata[0].description,
+        Some("Seconds to wait before giving up")
+    );
+    assert_eq!(metadata[1].description, None);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Grouped {
+    #[config_menu(group = "Network")]
+    host: String,
+    name: String,
+    #[config_menu(group = "Logging")]
+    level: u8,
+    #[config_menu(group = "Network")]
+    port: u16,
+}
+
+#[test]
+fn grouped_fields_sit_under_one_header_each() {
+    let mut controller = MenuController::new(Grouped::default());
+    let rows: Vec<_> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| (item.is_header, item.label.as_str()))
+        .collect();
+
+    assert_eq!(
+        rows,
+        [
+            (true, "General"),
+            (false, "name"),
+            (true, "Network"),
+            (false, "host"),
+            (false, "port"),
+            (true, "Logging"),
+            (false, "level"),
+        ]
+    );
+
+    assert_eq!(controller.menu_state.current_selection, 1);
+    controller.menu_state.next();
+    assert_eq!(controller.menu_state.current_selection, 3);
+}
+
+#[test]
+fn order_sorts_the_menu_but_not_the_file() {
+    let names: Vec<_> = Ordered::get_field_metadata()
+        .iter()
+        .map(|m| m.name)
+        .collect();
+    assert_eq!(names, ["retries", "tls", "name"]);
+
+    let saved = toml::to_string(&Ordered::default()).unwrap();
+    assert!(saved.find("name").unwrap() < saved.find("retries").unwrap());
+}
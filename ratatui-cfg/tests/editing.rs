@@ -0,0 +1,207 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+    std::path::PathBuf,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Tls {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    #[config_menu(readonly)]
+    id: u32,
+    #[config_menu(readonly)]
+    tls: Tls,
+}
+
+fn config() -> Config {
+    Config {
+        name: "server".to_string(),
+        id: 7,
+        tls: Tls {
+            enabled: true,
+            port: 443,
+        },
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ratatui-cfg-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn set_field_refuses_readonly_fields() {
+    let mut controller = MenuController::new(config());
+
+    assert!(controller.set_field(&["id"], "8").is_err());
+    assert!(controller.set_field(&["tls", "port"], "80").is_err());
+    assert_eq!(controller.config.id, 7);
+    assert_eq!(controller.config.tls.port, 443);
+}
+
+#[test]
+fn level_reset_keeps_readonly_fields() {
+    let mut controller = MenuController::new(config());
+
+    controller.reset_current_level_to_default().unwrap();
+
+    assert_eq!(controller.config.name, "");
+    assert_eq!(controller.config.id, 7);
+    assert_eq!(controller.config.tls.port, 443);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Inner {
+    #[config_menu(readonly)]
+    id: u32,
+    plain: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Outer {
+    inner: Inner,
+    #[config_menu(readonly)]
+    #[serde(rename = "the-id")]
+    the_id: u32,
+}
+
+fn outer() -> Outer {
+    Outer {
+        inner: Inner { id: 7, plain: 1 },
+        the_id: 7,
+    }
+}
+
+#[test]
+fn struct_writes_keep_readonly_fields_inside() {
+    let mut controller = MenuController::new(outer());
+
+    controller
+        .set_field(&["inner"], "id = 99\nplain = 2")
+        .unwrap();
+
+    assert_eq!(controller.config.inner.id, 7);
+    assert_eq!(controller.config.inner.plain, 2);
+}
+
+#[test]
+fn level_reset_keeps_renamed_readonly_fields() {
+    let mut controller = MenuController::new(outer());
+
+    controller.reset_current_level_to_default().unwrap();
+
+    assert_eq!(controller.config.the_id, 7);
+    assert_eq!(controller.config.inner.id, 7);
+    assert_eq!(controller.config.inner.plain, 0);
+}
+
+#[test]
+fn merge_skips_readonly_fields() {
+    let path = temp_path("merge-readonly.toml");
+    std::fs::write(&path, "name = \"merged\"\nid = 9\n\n[tls]\nport = 80\n").unwrap();
+
+    let mut controller = MenuController::new(config());
+    controller.merge_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(controller.config.name, "merged");
+    assert_eq!(controller.config.id, 7);
+    assert_eq!(controller.config.tls.port, 443);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Pool {
+    name: String,
+    #[config_menu(default = "8")]
+    size: u32,
+}
+
+#[test]
+fn reset_restores_defaults_and_can_be_undone() {
+    let mut controller = MenuController::new(Pool {
+        name: "workers".to_string(),
+        size: 32,
+    });
+
+    controller.reset_current_field().unwrap();
+    assert_eq!(controller.config.name, "");
+    controller.menu_state.next();
+    controller.reset_current_field().unwrap();
+    assert_eq!(controller.config.size, 8);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.size, 32);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Knobs {
+    #[config_menu(step = 0.5)]
+    gain: f32,
+    level: u8,
+}
+
+#[test]
+fn stepping_adds_the_step_size() {
+    let mut controller = MenuController::new(Knobs::default());
+
+    for _ in 0..3 {
+        controller.increment_field().unwrap();
+    }
+    assert_eq!(controller.config.gain, 1.5);
+    controller.decrement_field().unwrap();
+    assert_eq!(controller.config.gain, 1.0);
+}
+
+#[test]
+fn integer_steps_saturate() {
+    let mut controller = MenuController::new(Knobs {
+        gain: 0.0,
+        level: 255,
+    });
+    controller.menu_state.next();
+
+    controller.increment_field().unwrap();
+    assert_eq!(controller.config.level, 255);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Counters {
+    total: u128,
+}
+
+#[test]
+fn u128_steps_reach_its_maximum() {
+    let mut controller = MenuController::new(Counters {
+        total: u128::MAX - 1,
+    });
+
+    controller.increment_field().unwrap();
+    assert_eq!(controller.config.total, u128::MAX);
+    controller.increment_field().unwrap();
+    assert_eq!(controller.config.total, u128::MAX);
+    controller.decrement_field().unwrap();
+    controller.decrement_field().unwrap();
+    assert_eq!(controller.config.total, u128::MAX - 2);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.total, u128::MAX - 1);
+}
+
+#[test]
+fn u128_values_beyond_u64_are_set_and_undone() {
+    let mut controller = MenuController::new(Counters::default());
+    let wide = u64::MAX as u128 + 10;
+
+    controller.set_field(&["total"], &wide.to_string()).unwrap();
+    assert_eq!(controller.config.total, wide);
+    controller.set_field(&["total"], "5").unwrap();
+    controller.undo().unwrap();
+    assert_eq!(controller.config.total, 0);
+    controller.redo().unwrap();
+    assert_eq!(controller.config.total, 5);
+}
@@ -0,0 +1,155 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Form {
+    text: String,
+    count: u32,
+    offset: i32,
+    ratio: f64,
+}
+
+// Starts editing the `text` field holding `text`, with the cursor at the end.
+fn editing(text: &str) -> MenuController<Form> {
+    let mut controller = MenuController::new(Form {
+        text: text.to_string(),
+        ..Form::default()
+    });
+    controller.start_editing();
+    controller
+}
+
+#[test]
+fn cursor_moves_by_word() {
+    let mut controller = editing("foo bar  baz");
+
+    let mut stops = Vec::new();
+    for _ in 0..4 {
+        controller.move_cursor_word_left();
+        stops.push(controller.edit_cursor);
+    }
+    assert_eq!(stops, [9, 4, 0, 0]);
+
+    stops.clear();
+    for _ in 0..4 {
+        controller.move_cursor_word_right();
+        stops.push(controller.edit_cursor);
+    }
+    assert_eq!(stops, [4, 9, 12, 12]);
+}
+
+#[test]
+fn words_are_deleted_backwards() {
+    let mut controller = editing("alpha beta gamma");
+
+    controller.delete_word_backward();
+    assert_eq!(controller.edit_buffer, "alpha beta ");
+    controller.delete_word_backward();
+    assert_eq!(controller.edit_buffer, "alpha ");
+    assert_eq!(controller.edit_cursor, 6);
+
+    controller.move_cursor_home();
+    controller.delete_word_backward();
+    assert_eq!(controller.edit_buffer, "alpha ");
+}
+
+#[test]
+fn clearing_empties_the_buffer() {
+    let mut controller = editing("alpha beta");
+    controller.move_cursor_word_left();
+
+    controller.clear_to_start();
+    assert_eq!(controller.edit_buffer, "beta");
+    assert_eq!(controller.edit_cursor, 0);
+
+    controller.clear_line();
+    assert_eq!(controller.edit_buffer, "");
+    assert_eq!(controller.edit_cursor, 0);
+}
+
+#[test]
+fn home_and_end_reach_both_extremes() {
+    let mut controller = editing("https://example.com");
+
+    controller.move_cursor_home();
+    assert_eq!(controller.edit_cursor, 0);
+    controller.move_cursor_end();
+    assert_eq!(controller.edit_cursor, 19);
+}
+
+#[test]
+fn editing_works_across_multibyte_characters() {
+    let mut controller = editing("café🎉");
+    assert_eq!(controller.edit_cursor, 5);
+
+    controller.handle_backspace();
+    assert_eq!(controller.edit_buffer, "café");
+    controller.move_cursor_left();
+    controller.handle_backspace();
+    assert_eq!(controller.edit_buffer, "caé");
+    controller.handle_edit_input('🎉');
+    controller.move_cursor_end();
+    controller.handle_edit_input('!');
+    assert_eq!(controller.edit_buffer, "ca🎉é!");
+
+    controller.finish_editing().unwrap();
+    assert_eq!(controller.config.text, "ca🎉é!");
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Motd {
+    #[config_menu(multiline)]
+    banner: String,
+}
+
+#[test]
+fn multiline_values_commit_intact() {
+    let mut controller = MenuController::new(Motd::default());
+    assert!(controller.is_current_multiline());
+    controller.start_editing();
+
+    for c in "Welcome\nbe nice".chars() {
+        controller.handle_edit_input(c);
+    }
+    assert_eq!(controller.edit_cursor_position(), (1, 7));
+    controller.move_cursor_up();
+    assert_eq!(controller.edit_cursor_position(), (0, 7));
+
+    controller.finish_editing().unwrap();
+    assert_eq!(controller.config.banner, "Welcome\nbe nice");
+
+    controller.start_editing();
+    assert_eq!(controller.edit_buffer, "Welcome\nbe nice");
+}
+
+#[test]
+fn numeric_fields_filter_keystrokes() {
+    let mut controller = MenuController::new(Form::default());
+    controller.menu_state.select(1);
+    controller.start_editing();
+    controller.clear_line();
+    for c in "1a2-".chars() {
+        controller.handle_edit_input(c);
+    }
+    assert_eq!(controller.edit_buffer, "12");
+    controller.cancel_editing();
+
+    controller.menu_state.select(2);
+    controller.start_editing();
+    controller.clear_line();
+    for c in "-4-2".chars() {
+        controller.handle_edit_input(c);
+    }
+    assert_eq!(controller.edit_buffer, "-42");
+    controller.cancel_editing();
+
+    controller.menu_state.select(3);
+    controller.start_editing();
+    controller.clear_line();
+    for c in "1.5.e3x".chars() {
+        controller.handle_edit_input(c);
+    }
+    assert_eq!(controller.edit_buffer, "1.5e3");
+}
@@ -0,0 +1,70 @@
+use {
+    ratatui_cfg::{ConfigMenu, ConfigMenuTrait, MenuController},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+enum Mode {
+    #[default]
+    Fast,
+    Safe,
+    Off,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    mode: Mode,
+}
+
+#[test]
+fn cycling_past_the_last_variant_wraps() {
+    let mut controller = MenuController::new(Config { mode: Mode::Off });
+    assert_eq!(Mode::variants(), Some(&["Fast", "Safe", "Off"][..]));
+    assert!(controller.is_current_enum());
+
+    controller.cycle_enum(true).unwrap();
+    assert_eq!(controller.config.mode, Mode::Fast);
+    controller.cycle_enum(false).unwrap();
+    assert_eq!(controller.config.mode, Mode::Off);
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Logging {
+    level: LogLevel,
+}
+
+#[test]
+fn popup_picks_a_variant() {
+    let mut controller = MenuController::new(Logging::default());
+    controller.open_enum_popup().unwrap();
+
+    let popup = controller.popup.as_ref().unwrap();
+    assert_eq!(popup.options.len(), 5);
+    assert_eq!(popup.list_state.selected(), Some(2));
+
+    controller.popup_next();
+    controller.confirm_popup().unwrap();
+    assert_eq!(controller.config.level, LogLevel::Warn);
+    assert!(controller.popup.is_none());
+}
+
+#[test]
+fn closing_the_popup_keeps_the_value() {
+    let mut controller = MenuController::new(Logging::default());
+    controller.open_enum_popup().unwrap();
+    controller.popup_previous();
+    controller.close_popup();
+
+    assert_eq!(controller.config.level, LogLevel::Info);
+    assert!(controller.popup.is_none());
+}
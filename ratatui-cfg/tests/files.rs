@@ -0,0 +1,351 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController, SaveConflict},
+    serde::{Deserialize, Serialize},
+    std::path::PathBuf,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+struct Tls {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    retries: u32,
+    #[config_menu(secret)]
+    token: String,
+    tls: Tls,
+}
+
+fn config() -> Config {
+    Config {
+        name: "server".to_string(),
+        retries: 3,
+        token: "hunter2".to_string(),
+        tls: Tls {
+            enabled: true,
+            port: 443,
+        },
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ratatui-cfg-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn merge_reports_unknown_fields() {
+    let path = temp_path("merge-unknown.toml");
+    std::fs::write(&path, "retries = 4\nbogus = 1\n\n[tls]\nlegacy = true\n").unwrap();
+
+    let mut controller = MenuController::new(Config::default());
+    controller.merge_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(controller.config.retries, 4);
+    assert_eq!(
+        controller.status_message.as_deref(),
+        Some("Skipped unknown fields: bogus, tls.legacy")
+    );
+}
+
+#[test]
+fn merge_matches_renamed_fields_by_their_serialized_key() {
+    let path = temp_path("merge-renamed.toml");
+    std::fs::write(
+        &path,
+        "api-token = \"rotated\"\nadmin-password = \"changed\"\n",
+    )
+    .unwrap();
+
+    let mut controller = MenuController::new(Service::default());
+    controller.merge_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(controller.config.token, "rotated");
+    assert_eq!(controller.config.admin_password, "changed");
+    assert_eq!(controller.status_message, None);
+}
+
+#[test]
+fn export_round_trips_the_config() {
+    let controller = MenuController::new(config());
+    let exported = controller.export_current_format_string().unwrap();
+
+    assert_eq!(toml::from_str::<Config>(&exported).unwrap(), config());
+}
+
+#[test]
+fn export_uses_the_loaded_format() {
+    let path = temp_path("export.json");
+    std::fs::write(&path, serde_json::to_string(&config()).unwrap()).unwrap();
+
+    let controller = MenuController::<Config>::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let exported = controller.export_current_format_string().unwrap();
+
+    assert_eq!(serde_json::from_str::<Config>(&exported).unwrap(), config());
+}
+
+#[test]
+fn export_can_mask_secrets() {
+    let mut controller = MenuController::new(config());
+    controller.mask_secrets_on_export = true;
+    let exported: Config =
+        toml::from_str(&controller.export_current_format_string().unwrap()).unwrap();
+
+    assert_eq!(exported.token, "••••••");
+    assert_eq!(exported.name, "server");
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+#[serde(rename_all = "kebab-case")]
+struct Service {
+    #[config_menu(secret)]
+    #[serde(rename = "api-token")]
+    token: String,
+    #[config_menu(secret)]
+    admin_password: String,
+}
+
+#[test]
+fn export_masks_renamed_secrets() {
+    let mut controller = MenuController::new(Service {
+        token: "hunter2".to_string(),
+        admin_password: "swordfish".to_string(),
+    });
+    controller.mask_secrets_on_export = true;
+    let exported = controller.export_current_format_string().unwrap();
+
+    assert!(!exported.contains("hunter2"));
+    assert!(!exported.contains("swordfish"));
+    assert!(exported.contains("api-token = \"••••••\""));
+    assert!(exported.contains("admin-password = \"••••••\""));
+}
+
+#[test]
+fn safe_save_reports_external_changes() {
+    let path = temp_path("safe-save.toml");
+    std::fs::write(&path, toml::to_string(&config()).unwrap()).unwrap();
+
+    let mut controller = MenuController::<Config>::load(&path).unwrap();
+    std::fs::write(&path, "name = \"edited elsewhere\"\n").unwrap();
+    let result = controller.save_to_file_safe(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(result, Err(SaveConflict::ExternalChange)));
+}
+
+#[test]
+fn safe_save_to_another_existing_file_overwrites_it() {
+    let loaded = temp_path("safe-save-a.toml");
+    let other = temp_path("safe-save-b.toml");
+    std::fs::write(&loaded, toml::to_string(&config()).unwrap()).unwrap();
+    std::fs::write(&other, "name = \"unrelated\"\n").unwrap();
+
+    let mut controller = MenuController::<Config>::load(&loaded).unwrap();
+    let result = controller.save_to_file_safe(&other);
+    let saved = std::fs::read_to_string(&other).unwrap();
+    std::fs::remove_file(&loaded).unwrap();
+    std::fs::remove_file(&other).unwrap();
+
+    assert!(result.is_ok());
+    assert_eq!(toml::from_str::<Config>(&saved).unwrap(), config());
+}
+
+#[test]
+fn safe_save_without_a_loaded_file_saves_in_the_path_format() {
+    let path = temp_path("safe-save-new.json");
+    std::fs::write(&path, "{}").unwrap();
+
+    let mut controller = MenuController::new(config());
+    controller.save_to_file_safe(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(serde_json::from_str::<Config>(&saved).unwrap(), config());
+}
+
+#[test]
+fn json_round_trips() {
+    let path = temp_path("round-trip.json");
+    let mut controller = MenuController::new(config());
+
+    controller.save_to_json(&path).unwrap();
+    let loaded = MenuController::<Config>::load_from_json(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.config, config());
+}
+
+#[cfg(feature = "yaml")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+struct Profile {
+    nickname: Option<String>,
+    motto: Option<String>,
+    tls: Tls,
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_round_trips_nested_and_optional_fields() {
+    let path = temp_path("round-trip.yaml");
+    let profile = Profile {
+        nickname: Some("ace".to_string()),
+        motto: None,
+        tls: Tls {
+            enabled: true,
+            port: 8443,
+        },
+    };
+    let mut controller = MenuController::new(profile.clone());
+
+    controller.save_to_yaml(&path).unwrap();
+    let loaded = MenuController::<Profile>::load_from_yaml(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.config, profile);
+}
+
+#[cfg(feature = "ron")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+enum Transport {
+    #[default]
+    Tcp,
+    Quic,
+}
+
+#[cfg(feature = "ron")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+struct Link {
+    transport: Transport,
+    tls: Tls,
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn ron_round_trips_enums_and_nested_structs() {
+    let path = temp_path("round-trip.ron");
+    let link = Link {
+        transport: Transport::Quic,
+        tls: Tls {
+            enabled: true,
+            port: 4433,
+        },
+    };
+    let mut controller = MenuController::new(link.clone());
+
+    controller.save_to_ron(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    let loaded = MenuController::<Link>::load_from_ron(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(saved.contains('\n'));
+    assert_eq!(loaded.config, link);
+}
+
+#[test]
+fn save_and_load_pick_the_format_from_the_extension() {
+    let mut controller = MenuController::new(config());
+
+    for name in ["cfg.json", "cfg.toml"] {
+        let path = temp_path(name);
+        controller.save(&path).unwrap();
+        let loaded = MenuController::<Config>::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.config, config());
+    }
+
+    let json = temp_path("cfg-format.json");
+    controller.save(&json).unwrap();
+    let contents = std::fs::read_to_string(&json).unwrap();
+    std::fs::remove_file(&json).unwrap();
+    assert!(contents.trim_start().starts_with('{'));
+
+    assert!(controller.save(temp_path("cfg.ini")).is_err());
+}
+
+#[test]
+fn write_to_fills_any_writer() {
+    let controller = MenuController::new(config());
+    let mut buffer = Vec::new();
+
+    controller.write_to(&mut buffer).unwrap();
+    let parsed: Config = toml::from_str(std::str::from_utf8(&buffer).unwrap()).unwrap();
+
+    assert_eq!(parsed, config());
+}
+
+#[test]
+fn read_from_takes_any_reader() {
+    let bytes: &[u8] =
+        b"name = \"embedded\"\nretries = 1\ntoken = \"\"\n\n[tls]\nenabled = false\nport = 80\n";
+    let controller = MenuController::<Config>::read_from(bytes).unwrap();
+    assert_eq!(controller.config.name, "embedded");
+    assert_eq!(controller.config.tls.port, 80);
+
+    let invalid: &[u8] = &[0xff, 0xfe];
+    let error = MenuController::<Config>::read_from(invalid).err().unwrap();
+    assert!(error.to_string().contains("UTF-8"));
+}
+
+#[test]
+fn saves_replace_the_file_atomically() {
+    let path = temp_path("atomic.toml");
+    let tmp = temp_path("atomic.toml.tmp");
+    std::fs::write(&path, "old contents").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+    }
+
+    let mut controller = MenuController::new(config());
+    controller.save_to_file(&path).unwrap();
+    let saved: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(saved, config());
+    assert!(!tmp.exists());
+}
+
+#[test]
+fn a_failed_save_cleans_up_its_temp_file() {
+    // Renaming a file over a directory fails after the temp file is written.
+    let path = temp_path("atomic-dir.toml");
+    let tmp = temp_path("atomic-dir.toml.tmp");
+    std::fs::create_dir_all(&path).unwrap();
+
+    let mut controller = MenuController::new(config());
+    let result = controller.save_to_file(&path);
+    std::fs::remove_dir(&path).unwrap();
+
+    assert!(result.is_err());
+    assert!(!tmp.exists());
+}
+
+#[test]
+fn saving_twice_backs_up_the_first_version() {
+    let path = temp_path("backup.toml");
+    let backup = temp_path("backup.toml.bak");
+    let mut controller = MenuController::new(config());
+    controller.backup_on_save = true;
+
+    controller.save_to_file(&path).unwrap();
+    let first = std::fs::read_to_string(&path).unwrap();
+    controller.set_field(&["retries"], "9").unwrap();
+    controller.save_to_file(&path).unwrap();
+
+    let backed_up = std::fs::read_to_string(&backup).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&backup).unwrap();
+    assert_eq!(backed_up, first);
+}
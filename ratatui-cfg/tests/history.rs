@@ -0,0 +1,260 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+    std::{cell::RefCell, path::PathBuf, rc::Rc},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    retries: u32,
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ratatui-cfg-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn load_history_reports_entries_for_missing_fields() {
+    let path = temp_path("missing-fields.json");
+    let history = r#"{"head":2,"entries":[
+        {"field_path":["retries"],"old_value":0,"new_value":3},
+        {"field_path":["removed"],"old_value":1,"new_value":2}
+    ]}"#;
+    std::fs::write(&path, history).unwrap();
+
+    let mut controller = MenuController::new(Config::default());
+    controller.load_history(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(controller.history_entries().len(), 1);
+    assert!(controller.status_message.unwrap().contains("removed"));
+}
+
+fn three_edits() -> MenuController<Config> {
+    // Consecutive edits to one field merge, so alternate between two.
+    let mut controller = MenuController::new(Config::default());
+    controller.set_field(&["name"], "first").unwrap();
+    controller.set_field(&["retries"], "3").unwrap();
+    controller.set_field(&["name"], "second").unwrap();
+    controller
+}
+
+#[test]
+fn jump_to_history_moves_between_states() {
+    let mut controller = three_edits();
+
+    controller.jump_to_history(1).unwrap();
+    assert_eq!(controller.config.name, "first");
+    assert_eq!(controller.config.retries, 0);
+    let applied: Vec<bool> = controller
+        .history_entries()
+        .iter()
+        .map(|e| e.applied)
+        .collect();
+    assert_eq!(applied, [true, false, false]);
+
+    controller.jump_to_history(3).unwrap();
+    assert_eq!(controller.config.name, "second");
+}
+
+#[test]
+fn jump_to_history_reaches_the_initial_state() {
+    let mut controller = three_edits();
+
+    controller.jump_to_history(0).unwrap();
+
+    assert_eq!(controller.config.name, "");
+    assert!(!controller.is_dirty());
+    assert!(controller.jump_to_history(4).is_err());
+}
+
+#[test]
+fn an_edit_is_recorded_and_undone() {
+    let mut controller = MenuController::new(Config::default());
+    controller.set_field(&["retries"], "3").unwrap();
+
+    let entries = controller.history_entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].field_path, ["retries"]);
+    assert_eq!(
+        (entries[0].old_value.as_str(), entries[0].new_value.as_str()),
+        ("0", "3")
+    );
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.retries, 0);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Limits {
+    enabled: bool,
+    label: String,
+    max: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Service {
+    name: String,
+    #[serde(rename = "rate-limits")]
+    limits: Limits,
+}
+
+#[test]
+fn level_reset_is_a_single_undo_step() {
+    let mut controller = MenuController::new(Service::default());
+    controller.set_field(&["name"], "api").unwrap();
+    controller
+        .set_field(&["limits", "enabled"], "true")
+        .unwrap();
+    controller.set_field(&["limits", "label"], "burst").unwrap();
+    controller.set_field(&["limits", "max"], "50").unwrap();
+    controller.menu_state.select(1);
+    controller.enter_submenu().unwrap();
+
+    controller.reset_current_level_to_default().unwrap();
+    assert!(!controller.config.limits.enabled);
+    assert_eq!(controller.config.limits.label, "");
+    assert_eq!(controller.config.limits.max, 0);
+    assert_eq!(controller.config.name, "api");
+
+    controller.undo().unwrap();
+    assert!(controller.config.limits.enabled);
+    assert_eq!(controller.config.limits.label, "burst");
+    assert_eq!(controller.config.limits.max, 50);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Cluster {
+    nodes: Vec<Limits>,
+    fallback: Option<Limits>,
+}
+
+fn busy_limits() -> Limits {
+    Limits {
+        enabled: true,
+        label: "burst".to_string(),
+        max: 50,
+    }
+}
+
+#[test]
+fn level_reset_inside_a_vec_element_uses_the_element_default() {
+    let mut controller = MenuController::new(Cluster {
+        nodes: vec![busy_limits(), busy_limits()],
+        fallback: None,
+    });
+    controller.enter_submenu().unwrap();
+    controller.menu_state.select(1);
+    controller.enter_submenu().unwrap();
+
+    controller.reset_current_level_to_default().unwrap();
+    assert_eq!(controller.config.nodes.len(), 2);
+    assert_eq!(controller.config.nodes[0].label, "burst");
+    assert!(!controller.config.nodes[1].enabled);
+    assert_eq!(controller.config.nodes[1].label, "");
+    assert_eq!(controller.config.nodes[1].max, 0);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.nodes[1].max, 50);
+}
+
+#[test]
+fn level_reset_inside_an_option_keeps_it_set() {
+    let mut controller = MenuController::new(Cluster {
+        nodes: Vec::new(),
+        fallback: Some(busy_limits()),
+    });
+    controller.menu_state.select(1);
+    controller.enter_submenu().unwrap();
+
+    controller.reset_current_level_to_default().unwrap();
+    let fallback = controller.config.fallback.as_ref().unwrap();
+    assert_eq!(fallback.label, "");
+    assert_eq!(fallback.max, 0);
+}
+
+#[test]
+fn undo_restores_the_value_and_its_menu_item() {
+    let mut controller = MenuController::new(Config::default());
+    controller.undo().unwrap();
+    controller.redo().unwrap();
+
+    controller.set_field(&["retries"], "5").unwrap();
+    controller.undo().unwrap();
+    assert_eq!(controller.config.retries, 0);
+    assert_eq!(controller.menu_state.current_items()[1].value, "0");
+
+    controller.redo().unwrap();
+    assert_eq!(controller.config.retries, 5);
+    assert_eq!(controller.menu_state.current_items()[1].value, "5");
+}
+
+#[test]
+fn history_is_capped_at_its_limit() {
+    let mut controller = MenuController::new(Config::default());
+    for i in 0..150 {
+        let field = if i % 2 == 0 { "name" } else { "retries" };
+        controller.set_field(&[field], &i.to_string()).unwrap();
+    }
+    assert_eq!(controller.history_entries().len(), 100);
+
+    controller.set_history_limit(10).unwrap();
+    assert_eq!(controller.history_entries().len(), 10);
+
+    controller.undo().unwrap();
+    controller.redo().unwrap();
+    assert_eq!(controller.config.retries, 149);
+}
+
+#[test]
+fn dirty_follows_the_saved_point() {
+    let path = temp_path("dirty.toml");
+    let mut controller = MenuController::new(Config::default());
+    assert!(!controller.is_dirty());
+
+    controller.set_field(&["name"], "saved").unwrap();
+    assert!(controller.is_dirty());
+
+    controller.save_to_file(&path).unwrap();
+    assert!(!controller.is_dirty());
+
+    controller.set_field(&["retries"], "2").unwrap();
+    assert!(controller.is_dirty());
+    controller.undo().unwrap();
+    assert!(!controller.is_dirty());
+
+    let loaded = MenuController::<Config>::load_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(!loaded.is_dirty());
+}
+
+#[test]
+fn dirty_listener_fires_on_transitions_only() {
+    let path = temp_path("dirty-listener.toml");
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let mut controller = MenuController::new(Config::default());
+    let seen = Rc::clone(&changes);
+    controller.on_dirty_change(move |dirty| seen.borrow_mut().push(dirty));
+
+    controller.set_field(&["name"], "first").unwrap();
+    assert_eq!(*changes.borrow(), [true]);
+    controller.set_field(&["retries"], "1").unwrap();
+    assert_eq!(*changes.borrow(), [true]);
+
+    controller.save_to_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(*changes.borrow(), [true, false]);
+}
+
+#[test]
+fn repeated_edits_to_one_field_undo_together() {
+    let mut controller = MenuController::new(Config::default());
+    controller.set_field(&["retries"], "1").unwrap();
+    controller.set_field(&["retries"], "2").unwrap();
+    controller.set_field(&["retries"], "3").unwrap();
+    assert_eq!(controller.history_entries().len(), 1);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.retries, 0);
+}
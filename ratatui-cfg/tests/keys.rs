@@ -0,0 +1,204 @@
+#![cfg(feature = "crossterm")]
+
+use {
+    ratatui::{
+        Terminal,
+        backend::TestBackend,
+        crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    },
+    ratatui_cfg::{
+        ConfigMenu, ConfigMenuTrait, KeyBindings, KeyOutcome, MenuController, render_menu,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    retries: u32,
+    servers: Vec<String>,
+}
+
+fn press(controller: &mut MenuController<impl ConfigMenuTrait>, code: KeyCode) -> KeyOutcome {
+    controller.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn screen<T: ConfigMenuTrait>(controller: &mut MenuController<T>) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 24)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, controller, frame.area()))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    (0..24)
+        .map(|y| {
+            (0..120)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+fn custom_bindings() -> KeyBindings {
+    KeyBindings {
+        save: 'w',
+        quit: 'z',
+        ..KeyBindings::default()
+    }
+}
+
+#[test]
+fn custom_bindings_dispatch() {
+    let mut controller = MenuController::with_keybindings(Config::default(), custom_bindings());
+
+    assert_eq!(press(&mut controller, KeyCode::Char('w')), KeyOutcome::Save);
+    assert_eq!(press(&mut controller, KeyCode::Char('z')), KeyOutcome::Quit);
+    assert_ne!(press(&mut controller, KeyCode::Char('s')), KeyOutcome::Save);
+}
+
+#[test]
+fn help_line_shows_custom_bindings() {
+    let mut controller = MenuController::new(Config::default());
+    controller.set_keybindings(custom_bindings());
+
+    let screen = screen(&mut controller);
+    assert!(screen.contains("w: Save"));
+    assert!(screen.contains("z: Quit"));
+    assert_eq!(controller.keybindings(), &custom_bindings());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Video {
+    width: u32,
+    quality: u32,
+}
+
+#[test]
+fn uppercase_letters_jump_past_action_keys() {
+    let mut controller = MenuController::new(Video::default());
+
+    assert_eq!(press(&mut controller, KeyCode::Char('q')), KeyOutcome::Quit);
+    press(&mut controller, KeyCode::Char('Q'));
+
+    assert_eq!(
+        controller.menu_state.get_current_item().unwrap().key,
+        "quality"
+    );
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+enum Speed {
+    #[default]
+    Slow,
+    Normal,
+    Fast,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Fan {
+    speed: Speed,
+}
+
+#[test]
+fn arrows_cycle_enum_variants() {
+    let mut controller = MenuController::new(Fan::default());
+    assert!(screen(&mut controller).contains("Left/Right: Change"));
+
+    for _ in 0..4 {
+        press(&mut controller, KeyCode::Right);
+    }
+    assert_eq!(controller.config.speed, Speed::Normal);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.speed, Speed::Slow);
+}
+
+#[test]
+fn enum_cycling_can_stop_at_the_ends() {
+    let mut controller = MenuController::new(Fan::default());
+    controller.wrap_enum_cycling = false;
+
+    press(&mut controller, KeyCode::Left);
+    assert_eq!(controller.config.speed, Speed::Slow);
+    for _ in 0..3 {
+        press(&mut controller, KeyCode::Right);
+    }
+    assert_eq!(controller.config.speed, Speed::Fast);
+}
+
+#[test]
+fn default_keys_drive_the_menu() {
+    let mut controller = MenuController::new(Config::default());
+
+    press(&mut controller, KeyCode::Down);
+    assert_eq!(controller.menu_state.current_selection, 1);
+    press(&mut controller, KeyCode::Enter);
+    assert!(controller.editing_mode);
+    press(&mut controller, KeyCode::Backspace);
+    press(&mut controller, KeyCode::Char('7'));
+    press(&mut controller, KeyCode::Enter);
+    assert!(!controller.editing_mode);
+    assert_eq!(controller.config.retries, 7);
+
+    press(&mut controller, KeyCode::Enter);
+    press(&mut controller, KeyCode::Char('9'));
+    press(&mut controller, KeyCode::Esc);
+    assert!(!controller.editing_mode);
+    assert_eq!(controller.config.retries, 7);
+
+    assert_eq!(press(&mut controller, KeyCode::Char('s')), KeyOutcome::Save);
+    assert_eq!(
+        press(&mut controller, KeyCode::Char('r')),
+        KeyOutcome::Reload
+    );
+    assert_eq!(press(&mut controller, KeyCode::Char('q')), KeyOutcome::Quit);
+}
+
+#[test]
+fn each_key_reports_what_happened() {
+    let mut controller = MenuController::new(Config::default());
+
+    assert_eq!(
+        press(&mut controller, KeyCode::Enter),
+        KeyOutcome::EditStarted
+    );
+    assert_eq!(
+        press(&mut controller, KeyCode::Char('x')),
+        KeyOutcome::Handled
+    );
+    assert_eq!(
+        press(&mut controller, KeyCode::Enter),
+        KeyOutcome::FieldChanged {
+            path: vec!["name".to_string()]
+        }
+    );
+    press(&mut controller, KeyCode::Enter);
+    assert_eq!(
+        press(&mut controller, KeyCode::Esc),
+        KeyOutcome::EditCancelled
+    );
+
+    press(&mut controller, KeyCode::Down);
+    assert_eq!(
+        press(&mut controller, KeyCode::Right),
+        KeyOutcome::FieldChanged {
+            path: vec!["retries".to_string()]
+        }
+    );
+    press(&mut controller, KeyCode::Enter);
+    press(&mut controller, KeyCode::Backspace);
+    assert!(matches!(
+        press(&mut controller, KeyCode::Enter),
+        KeyOutcome::Failed { .. }
+    ));
+    press(&mut controller, KeyCode::Esc);
+
+    press(&mut controller, KeyCode::Down);
+    assert_eq!(
+        press(&mut controller, KeyCode::Enter),
+        KeyOutcome::EnteredSubmenu
+    );
+    assert_eq!(press(&mut controller, KeyCode::Esc), KeyOutcome::WentBack);
+    assert_eq!(press(&mut controller, KeyCode::F(5)), KeyOutcome::Ignored);
+}
@@ -0,0 +1,131 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    tags: Vec<String>,
+    numbers: Vec<i32>,
+}
+
+fn tags(tags: &[&str]) -> MenuController<Config> {
+    MenuController::new(Config {
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        numbers: vec![1, 2, 3],
+    })
+}
+
+#[test]
+fn vec_elements_are_listed_and_edited() {
+    let mut controller = tags(&["a", "b", "c"]);
+    controller.enter_submenu().unwrap();
+
+    let labels: Vec<_> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.label.clone())
+        .collect();
+    assert_eq!(labels, ["[0]", "[1]", "[2]"]);
+
+    controller.menu_state.select(2);
+    controller.start_editing();
+    controller.clear_line();
+    for c in "z".chars() {
+        controller.handle_edit_input(c);
+    }
+    controller.finish_editing().unwrap();
+
+    assert_eq!(controller.config.tags, ["a", "b", "z"]);
+}
+
+fn remove_at(controller: &mut MenuController<Config>, index: usize) {
+    controller.menu_state.select(index);
+    controller.remove_vec_element().unwrap();
+}
+
+#[test]
+fn removing_the_middle_element_keeps_the_selection() {
+    let mut controller = tags(&["a", "b", "c"]);
+    controller.enter_submenu().unwrap();
+
+    remove_at(&mut controller, 1);
+    assert_eq!(controller.config.tags, ["a", "c"]);
+    assert_eq!(controller.menu_state.current_selection, 1);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.tags, ["a", "b", "c"]);
+}
+
+#[test]
+fn removing_the_last_element_moves_the_selection_up() {
+    let mut controller = tags(&["a", "b", "c"]);
+    controller.enter_submenu().unwrap();
+
+    remove_at(&mut controller, 2);
+    assert_eq!(controller.config.tags, ["a", "b"]);
+    assert_eq!(controller.menu_state.current_selection, 1);
+}
+
+#[test]
+fn removing_the_only_element_leaves_the_list() {
+    let mut controller = tags(&["a"]);
+    controller.enter_submenu().unwrap();
+
+    remove_at(&mut controller, 0);
+    assert!(controller.config.tags.is_empty());
+    assert!(controller.menu_state.get_current_level_path().is_empty());
+}
+
+#[test]
+fn moving_elements_swaps_neighbours() {
+    let mut controller = tags(&[]);
+    controller.menu_state.select(1);
+    controller.enter_submenu().unwrap();
+
+    controller.move_vec_element_down().unwrap();
+    assert_eq!(controller.config.numbers, [2, 1, 3]);
+    assert_eq!(controller.menu_state.current_selection, 1);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.numbers, [1, 2, 3]);
+}
+
+#[test]
+fn moving_past_either_end_does_nothing() {
+    let mut controller = tags(&[]);
+    controller.menu_state.select(1);
+    controller.enter_submenu().unwrap();
+
+    controller.move_vec_element_up().unwrap();
+    controller.menu_state.select_last();
+    controller.move_vec_element_down().unwrap();
+
+    assert_eq!(controller.config.numbers, [1, 2, 3]);
+    assert!(controller.history_entries().is_empty());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Env {
+    vars: BTreeMap<String, String>,
+}
+
+#[test]
+fn map_entries_are_added_edited_and_removed() {
+    let mut controller = MenuController::new(Env::default());
+    controller.enter_submenu().unwrap();
+
+    controller.insert_map_entry("PATH".to_string()).unwrap();
+    assert_eq!(
+        controller.menu_state.get_current_item().unwrap().key,
+        "PATH"
+    );
+    controller.set_field(&["vars", "PATH"], "/usr/bin").unwrap();
+    assert_eq!(controller.config.vars["PATH"], "/usr/bin");
+    assert!(controller.insert_map_entry("PATH".to_string()).is_err());
+
+    controller.remove_vec_element().unwrap();
+    assert!(controller.config.vars.is_empty());
+}
@@ -0,0 +1,173 @@
+use {
+    ratatui::{Terminal, backend::TestBackend},
+    ratatui_cfg::{ConfigMenu, MenuController, render_menu},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Network {
+    host: String,
+    port: u16,
+    admin_port: u16,
+    name: String,
+    retries: u32,
+    timeout: u32,
+    user: String,
+    password: String,
+    proxy_port: u16,
+    region: String,
+}
+
+#[test]
+fn paging_clamps_at_the_ends() {
+    let mut controller = MenuController::new(Network::default());
+
+    controller.menu_state.page_down(4);
+    assert_eq!(controller.menu_state.current_selection, 4);
+    controller.menu_state.page_down(4);
+    controller.menu_state.page_down(4);
+    assert_eq!(controller.menu_state.current_selection, 9);
+
+    controller.menu_state.page_up(4);
+    assert_eq!(controller.menu_state.current_selection, 5);
+    controller.menu_state.page_up(20);
+    assert_eq!(controller.menu_state.current_selection, 0);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Five {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Empty {}
+
+#[test]
+fn first_and_last_jump_to_the_ends() {
+    let mut controller = MenuController::new(Five::default());
+
+    controller.menu_state.select_last();
+    assert_eq!(controller.menu_state.current_selection, 4);
+    assert_eq!(controller.menu_state.list_state.selected(), Some(4));
+
+    controller.menu_state.select_first();
+    assert_eq!(controller.menu_state.current_selection, 0);
+    assert_eq!(controller.menu_state.list_state.selected(), Some(0));
+}
+
+#[test]
+fn first_and_last_ignore_an_empty_menu() {
+    let mut controller = MenuController::new(Empty {});
+
+    controller.menu_state.select_last();
+    controller.menu_state.select_first();
+    assert!(controller.menu_state.get_current_item().is_none());
+}
+
+#[test]
+fn search_narrows_the_fields_until_cleared() {
+    let mut controller = MenuController::new(Network::default());
+    controller.start_search();
+    for c in "PORT".chars() {
+        controller.handle_search_input(c);
+    }
+
+    let keys: Vec<_> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.key.as_str())
+        .collect();
+    assert_eq!(keys, ["port", "admin_port", "proxy_port"]);
+    controller.menu_state.next();
+    assert_eq!(
+        controller.menu_state.get_current_item().unwrap().key,
+        "admin_port"
+    );
+
+    controller.cancel_search();
+    assert_eq!(controller.menu_state.current_items().len(), 10);
+}
+
+fn draw(controller: &mut MenuController<Network>) {
+    let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, controller, frame.area()))
+        .unwrap();
+}
+
+#[test]
+fn clicks_select_and_then_activate() {
+    let mut controller = MenuController::new(Network::default());
+    draw(&mut controller);
+
+    // The breadcrumb pane takes three rows and the list's border one more.
+    controller.handle_mouse(10, 6).unwrap();
+    assert_eq!(controller.menu_state.current_selection, 2);
+    assert!(!controller.editing_mode);
+
+    controller.handle_mouse(10, 6).unwrap();
+    assert!(controller.editing_mode);
+}
+
+#[test]
+fn clicks_outside_the_list_are_ignored() {
+    let mut controller = MenuController::new(Network::default());
+    draw(&mut controller);
+
+    controller.handle_mouse(10, 1).unwrap();
+    assert_eq!(controller.menu_state.current_selection, 0);
+}
+
+#[test]
+fn scrolling_moves_the_selection() {
+    let mut controller = MenuController::new(Network::default());
+
+    for _ in 0..3 {
+        controller.menu_state.scroll_down();
+    }
+    assert_eq!(controller.menu_state.current_selection, 3);
+    controller.menu_state.scroll_up();
+    assert_eq!(controller.menu_state.current_selection, 2);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Gateway {
+    enabled: bool,
+    network: Network,
+    label: String,
+}
+
+#[test]
+fn leaving_a_submenu_keeps_the_parent_level_as_it_was() {
+    let mut controller = MenuController::new(Gateway::default());
+    controller.menu_state.select(1);
+    let breadcrumb = controller.menu_state.breadcrumb.clone();
+    let items = controller.menu_state.current_items().as_ptr();
+    let keys: Vec<String> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.key.clone())
+        .collect();
+
+    controller.enter_submenu().unwrap();
+    controller.menu_state.select(4);
+    assert_eq!(controller.menu_state.breadcrumb.len(), breadcrumb.len() + 1);
+    controller.menu_state.go_back();
+
+    assert_eq!(controller.menu_state.current_selection, 1);
+    assert_eq!(controller.menu_state.breadcrumb, breadcrumb);
+    assert_eq!(controller.menu_state.current_items().as_ptr(), items);
+    let after: Vec<String> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.key.clone())
+        .collect();
+    assert_eq!(after, keys);
+}
@@ -0,0 +1,45 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    port: Option<u16>,
+    label: Option<String>,
+    pair: Option<(u8, u8)>,
+}
+
+#[test]
+fn option_toggles_between_none_and_some() {
+    let mut controller = MenuController::new(Config::default());
+    assert!(controller.is_current_option());
+
+    controller.toggle_option().unwrap();
+    assert_eq!(controller.config.port, Some(0));
+    controller.toggle_option().unwrap();
+    assert_eq!(controller.config.port, None);
+}
+
+fn values(controller: &MenuController<Config>) -> Vec<String> {
+    controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.value.clone())
+        .collect()
+}
+
+#[test]
+fn option_display_keeps_inner_parentheses() {
+    let controller = MenuController::new(Config {
+        port: None,
+        label: Some("Some(weird)".to_string()),
+        pair: Some((1, 2)),
+    });
+
+    assert_eq!(
+        values(&controller),
+        ["<not set>", "\"Some(weird)\"", "(1, 2)"]
+    );
+}
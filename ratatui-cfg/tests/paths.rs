@@ -0,0 +1,126 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Tls {
+    enabled: bool,
+    port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Server {
+    host: String,
+    tls: Tls,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    server: Server,
+}
+
+#[test]
+fn toggle_boolean_at_path_flips_a_nested_field() {
+    let mut controller = MenuController::new(Config::default());
+
+    assert_eq!(
+        controller.toggle_boolean_at_path(&["server", "tls", "enabled"]),
+        Ok(true)
+    );
+    assert!(controller.config.server.tls.enabled);
+    assert!(
+        controller
+            .toggle_boolean_at_path(&["server", "tls", "port"])
+            .is_err()
+    );
+
+    controller.undo().unwrap();
+    assert!(!controller.config.server.tls.enabled);
+}
+
+#[test]
+fn parse_value_at_path_checks_without_writing() {
+    let controller = MenuController::new(Config::default());
+
+    assert!(
+        controller
+            .parse_value_at_path(&["server", "tls", "port"], "8443")
+            .is_ok()
+    );
+    assert!(
+        controller
+            .parse_value_at_path(&["server", "tls", "port"], "port")
+            .is_err()
+    );
+    assert_eq!(controller.config.server.tls.port, 0);
+}
+
+#[test]
+fn undo_reverts_a_field_two_levels_down() {
+    let mut controller = MenuController::new(Config::default());
+    controller
+        .set_field(&["server", "tls", "port"], "8443")
+        .unwrap();
+    assert_eq!(controller.config.server.tls.port, 8443);
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.server.tls.port, 0);
+    controller.redo().unwrap();
+    assert_eq!(controller.config.server.tls.port, 8443);
+}
+
+#[test]
+fn fields_are_counted_across_the_tree() {
+    let mut controller = MenuController::new(Config::default());
+    assert_eq!(controller.count_fields(), 4);
+    assert_eq!(controller.count_dirty_fields(), 0);
+
+    controller
+        .set_field(&["server", "tls", "port"], "443")
+        .unwrap();
+    assert_eq!(controller.count_dirty_fields(), 1);
+    controller.set_field(&["name"], "edge").unwrap();
+    assert_eq!(controller.count_dirty_fields(), 2);
+}
+
+#[test]
+fn get_field_reads_top_level_and_nested_values() {
+    let controller = MenuController::new(Config {
+        name: "edge".to_string(),
+        server: Server {
+            tls: Tls {
+                port: 8443,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    });
+
+    assert_eq!(controller.get_field(&["name"]).as_deref(), Some("edge"));
+    assert_eq!(
+        controller.get_field(&["server", "tls", "port"]).as_deref(),
+        Some("8443")
+    );
+    assert_eq!(controller.get_field(&["server", "missing"]), None);
+    assert_eq!(controller.get_field(&["nothing"]), None);
+}
+
+#[test]
+fn set_field_writes_records_and_refreshes() {
+    let mut controller = MenuController::new(Config::default());
+
+    controller.set_field(&["name"], "edge").unwrap();
+    controller.set_field(&["server", "host"], "db").unwrap();
+    assert_eq!(controller.config.name, "edge");
+    assert_eq!(controller.config.server.host, "db");
+    assert_eq!(controller.menu_state.current_items()[0].value, "\"edge\"");
+    assert!(controller.set_field(&["server", "missing"], "x").is_err());
+
+    controller.undo().unwrap();
+    assert_eq!(controller.config.server.host, "");
+    controller.undo().unwrap();
+    assert_eq!(controller.config.name, "");
+    assert_eq!(controller.menu_state.current_items()[0].value, "\"\"");
+}
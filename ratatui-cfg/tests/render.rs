@@ -0,0 +1,451 @@
+use {
+    ratatui::{
+        Terminal,
+        backend::TestBackend,
+        style::{Color, Modifier},
+    },
+    ratatui_cfg::{
+        ConfigMenu, ConfigMenuTrait, MenuController, MenuLayout, MenuTheme, TreeConnectors,
+        render_menu,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Audio {
+    #[config_menu(min = 0, max = 100)]
+    volume: u32,
+    #[config_menu(secret, min = 0, max = 9999)]
+    pin: u32,
+}
+
+fn screen<T: ConfigMenuTrait>(
+    controller: &mut MenuController<T>,
+    width: u16,
+    height: u16,
+) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, controller, frame.area()))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
+
+#[test]
+fn slider_shows_the_value() {
+    let mut controller = MenuController::new(Audio {
+        volume: 42,
+        pin: 1234,
+    });
+
+    assert!(screen(&mut controller, 80, 24).contains("42 [0..100]"));
+}
+
+#[test]
+fn slider_is_hidden_for_secret_fields() {
+    let mut controller = MenuController::new(Audio {
+        volume: 42,
+        pin: 1234,
+    });
+    controller.menu_state.next();
+
+    assert!(!screen(&mut controller, 80, 24).contains("1234"));
+
+    let error = controller.set_field(&["pin"], "98x7").unwrap_err();
+    assert!(!error.contains("98x7"));
+    assert!(!screen(&mut controller, 80, 24).contains("98x7"));
+
+    let error = controller.set_field(&["pin"], "98765432109").unwrap_err();
+    assert!(!error.contains("98765432109"));
+    assert!(error.contains("out of range for u32"));
+
+    controller.set_field(&["pin"], "98765").unwrap();
+    assert!(!screen(&mut controller, 80, 24).contains("98765"));
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ConfigMenu)]
+enum Level {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Logging {
+    level: Level,
+}
+
+fn segmented() -> MenuController<Logging> {
+    let mut controller = MenuController::new(Logging::default());
+    controller.layout = MenuLayout {
+        segmented_enums: true,
+        ..MenuLayout::default()
+    };
+    controller
+}
+
+#[test]
+fn segmented_enum_highlights_the_active_variant() {
+    let mut controller = segmented();
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, &mut controller, frame.area()))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let row = (0..24)
+        .find(|&y| {
+            let line: String = (0..80).map(|x| buffer[(x, y)].symbol()).collect();
+            line.contains("[ Debug | Info | Warn ]")
+        })
+        .unwrap();
+    let reversed: String = (0..80)
+        .filter(|&x| buffer[(x, row)].modifier.contains(Modifier::REVERSED))
+        .map(|x| buffer[(x, row)].symbol())
+        .collect();
+
+    assert_eq!(reversed, "Info");
+}
+
+#[test]
+fn segmented_enum_falls_back_when_narrow() {
+    let mut controller = segmented();
+    let screen = screen(&mut controller, 24, 24);
+
+    assert!(screen.contains("level: Info"));
+    assert!(!screen.contains("Debug"));
+}
+
+#[test]
+fn clicking_a_segment_picks_the_variant() {
+    let mut controller = segmented();
+    let screen = screen(&mut controller, 80, 24);
+    let (row, line) = screen
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.contains("| Warn"))
+        .unwrap();
+    let column = line.find("Warn").unwrap();
+
+    controller.handle_mouse(column as u16, row as u16).unwrap();
+
+    assert_eq!(controller.config.level, Level::Warn);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Banner {
+    title: String,
+    #[config_menu(render_markup)]
+    motd: String,
+}
+
+#[test]
+fn markup_values_render_styled() {
+    // The selected row is drawn bold anyway, so check the one below it.
+    let mut controller = MenuController::new(Banner {
+        title: "news".to_string(),
+        motd: "<b>hello</b> world".to_string(),
+    });
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, &mut controller, frame.area()))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let (x, y) = (0..24)
+        .flat_map(|y| (0..80).map(move |x| (x, y)))
+        .find(|&(x, y)| {
+            (x..x + 5)
+                .map(|x| buffer[(x.min(79), y)].symbol())
+                .collect::<String>()
+                == "hello"
+        })
+        .unwrap();
+    let bold: String = (x..80)
+        .filter(|&x| buffer[(x, y)].modifier.contains(Modifier::BOLD))
+        .map(|x| buffer[(x, y)].symbol())
+        .collect();
+
+    assert_eq!(bold, "hello");
+}
+
+#[test]
+fn malformed_markup_renders_literally() {
+    let mut controller = MenuController::new(Banner {
+        title: "news".to_string(),
+        motd: "<b>hello".to_string(),
+    });
+
+    assert!(screen(&mut controller, 80, 24).contains("<b>hello"));
+}
+
+#[test]
+fn hidden_values_show_only_in_the_status() {
+    let mut controller = MenuController::new(Banner {
+        title: "news".to_string(),
+        motd: "hello".to_string(),
+    });
+    controller.hide_values = true;
+    let screen = screen(&mut controller, 80, 24);
+
+    assert!(!screen.contains("hello"));
+    assert_eq!(screen.matches("news").count(), 1);
+    assert!(screen.contains("title: \"news\""));
+}
+
+#[test]
+fn history_panel_lists_recent_edits() {
+    let mut controller = MenuController::new(Audio::default());
+    controller.set_field(&["volume"], "10").unwrap();
+    controller.set_field(&["pin"], "5").unwrap();
+    controller.show_history = true;
+
+    let recent = controller.recent_edits(1);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].field_path(), ["pin"]);
+
+    let screen = screen(&mut controller, 100, 24);
+    assert!(screen.contains("volume: 0 -> 10"));
+    assert!(screen.contains("pin: •••••• -> ••••••"));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Credentials {
+    user: String,
+    #[config_menu(secret)]
+    token: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Account {
+    credentials: Credentials,
+}
+
+#[test]
+fn history_panel_masks_secrets_in_struct_edits() {
+    let mut controller = MenuController::new(Account {
+        credentials: Credentials {
+            user: "admin".to_string(),
+            token: "hunter2".to_string(),
+        },
+    });
+    controller
+        .set_field(&["credentials"], "user = \"root\"\ntoken = \"qzj8vk\"")
+        .unwrap();
+    controller.reset_current_level_to_default().unwrap();
+    controller.show_history = true;
+    let screen = screen(&mut controller, 160, 24);
+
+    assert!(screen.contains("root"));
+    assert!(!screen.contains("hunter2"));
+    assert!(!screen.contains("qzj8vk"));
+}
+
+#[test]
+fn secret_strings_are_masked() {
+    let mut controller = MenuController::new(Credentials {
+        user: "admin".to_string(),
+        token: "qzj8vk".to_string(),
+    });
+    controller.menu_state.next();
+    let screen = screen(&mut controller, 80, 24);
+
+    assert!(!screen.contains("qzj8vk"));
+    let row = screen
+        .lines()
+        .find(|line| line.contains("token: "))
+        .unwrap();
+    let value = row.split("token: ").nth(1).unwrap();
+    assert!(value.starts_with("••••••"));
+    assert!(!value.contains(['q', 'z', 'j', '8', 'v', 'k']));
+    assert!(
+        controller
+            .export_current_format_string()
+            .unwrap()
+            .contains("qzj8vk")
+    );
+}
+
+#[test]
+fn custom_theme_colors_the_menu() {
+    assert_eq!(MenuTheme::default().highlight_fg, Color::Yellow);
+
+    let mut controller = MenuController::new(Audio::default());
+    controller.theme = Some(MenuTheme {
+        breadcrumb_fg: Color::Green,
+        highlight_fg: Color::Magenta,
+        ..MenuTheme::default()
+    });
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal
+        .draw(|frame| render_menu(frame, &mut controller, frame.area()))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let row = (0..24)
+        .find(|&y| (0..80).any(|x| buffer[(x, y)].symbol() == ">"))
+        .unwrap();
+    let x = (0..80).find(|&x| buffer[(x, row)].symbol() == ">").unwrap();
+    assert_eq!(buffer[(x, row)].fg, Color::Magenta);
+    assert_eq!(buffer[(1, 1)].fg, Color::Green);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Mixer {
+    master: Audio,
+    channels: Vec<u8>,
+}
+
+#[test]
+fn custom_symbols_are_drawn() {
+    let mut controller = MenuController::new(Mixer::default());
+    controller.theme = Some(MenuTheme {
+        highlight_symbol: "→ ".to_string(),
+        submenu_indicator: " ▸".to_string(),
+        vec_indicator: " ≡".to_string(),
+        ..MenuTheme::default()
+    });
+    let screen = screen(&mut controller, 80, 24);
+
+    assert!(screen.contains("→ master: "));
+    assert!(screen.contains("} ▸"));
+    assert!(screen.contains("channels: [] ≡"));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Studio {
+    mixer: Mixer,
+}
+
+#[test]
+fn nested_items_are_indented_by_depth() {
+    let mut controller = MenuController::new(Studio::default());
+    controller.theme = Some(MenuTheme {
+        indent_width: 2,
+        tree_connectors: Some(TreeConnectors::UNICODE),
+        ..MenuTheme::default()
+    });
+
+    let top = screen(&mut controller, 80, 24);
+    assert!(top.contains("│>> mixer: "));
+
+    controller.enter_submenu().unwrap();
+    let first = screen(&mut controller, 80, 24);
+    assert!(first.contains("│>> ├─master: "));
+    assert!(first.contains("│   └─channels: "));
+
+    controller.enter_submenu().unwrap();
+    let second = screen(&mut controller, 80, 24);
+    assert!(second.contains("│>>   ├─volume: "));
+    assert!(second.contains("│     └─pin: "));
+
+    controller.theme = Some(MenuTheme {
+        indent_width: 2,
+        tree_connectors: Some(TreeConnectors::ASCII),
+        ..MenuTheme::default()
+    });
+    assert!(screen(&mut controller, 80, 24).contains("│     `-pin: "));
+    controller.theme = Some(MenuTheme {
+        indent_width: 2,
+        ..MenuTheme::default()
+    });
+    assert!(screen(&mut controller, 80, 24).contains("│>>     volume: "));
+}
+
+#[test]
+fn details_pane_shows_the_selected_type() {
+    let mut controller = MenuController::new(Mixer::default());
+    controller.menu_state.next();
+
+    let screen = screen(&mut controller, 80, 24);
+    assert!(screen.contains("Details"));
+    assert!(screen.contains("Type: Vec<u8>"));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Dozen {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    f: u8,
+    g: u8,
+    h: u8,
+    i: u8,
+    j: u8,
+    k: u8,
+    l: u8,
+}
+
+#[test]
+fn settings_title_counts_the_position() {
+    let mut controller = MenuController::new(Dozen::default());
+    controller.menu_state.select(2);
+
+    assert!(screen(&mut controller, 80, 24).contains("Settings (3/12)"));
+}
+
+#[test]
+fn hidden_panes_are_not_drawn() {
+    let mut controller = MenuController::new(Audio::default());
+    assert!(screen(&mut controller, 80, 24).contains("Help"));
+
+    controller.layout = MenuLayout {
+        show_breadcrumb: false,
+        show_help: false,
+        ..MenuLayout::default()
+    };
+    let screen = screen(&mut controller, 80, 24);
+    assert!(!screen.contains("Help"));
+    assert!(!screen.contains("Navigate"));
+    assert!(!screen.contains("Navigation"));
+}
+
+#[test]
+fn aligned_values_share_a_column() {
+    let mut controller = MenuController::new(Banner {
+        title: "news".to_string(),
+        motd: "hello".to_string(),
+    });
+    controller.layout = MenuLayout {
+        align_values: true,
+        ..MenuLayout::default()
+    };
+    let screen = screen(&mut controller, 80, 24);
+
+    let column = |value: &str| {
+        let line = screen.lines().find(|line| line.contains(value)).unwrap();
+        line[..line.find(value).unwrap()].chars().count()
+    };
+    assert_eq!(column("\"news\""), column("hello"));
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Toggles {
+    enabled: bool,
+    verbose: bool,
+}
+
+#[test]
+fn booleans_render_as_checkboxes() {
+    let mut controller = MenuController::new(Toggles {
+        enabled: true,
+        verbose: false,
+    });
+    let screen = screen(&mut controller, 80, 24);
+
+    assert!(screen.contains("enabled: [x]"));
+    assert!(screen.contains("verbose: [ ]"));
+    assert!(!screen.contains("true"));
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "crossterm")]
+
+use {
+    ratatui::{
+        Terminal,
+        backend::TestBackend,
+        crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    },
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+    std::path::PathBuf,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    retries: u32,
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ratatui-cfg-{}-{}", std::process::id(), name))
+}
+
+fn run_script(controller: MenuController<Config>, events: Vec<Event>) -> Config {
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    let mut events = events.into_iter();
+
+    controller
+        .run_with_events(&mut terminal, || {
+            Ok(events.next().expect("script ended before quit"))
+        })
+        .unwrap()
+}
+
+#[test]
+fn scripted_events_edit_until_quit() {
+    let config = run_script(
+        MenuController::new(Config::default()),
+        vec![
+            key(KeyCode::Down),
+            key(KeyCode::Right),
+            key(KeyCode::Right),
+            key(KeyCode::Char('s')),
+            key(KeyCode::Char('q')),
+        ],
+    );
+
+    assert_eq!(config.retries, 2);
+}
+
+#[test]
+fn save_and_reload_use_the_loaded_file() {
+    let path = temp_path("run.toml");
+    std::fs::write(&path, "name = \"edge\"\nretries = 1\n").unwrap();
+
+    let saved = run_script(
+        MenuController::load(&path).unwrap(),
+        vec![
+            key(KeyCode::Down),
+            key(KeyCode::Right),
+            key(KeyCode::Char('s')),
+            key(KeyCode::Right),
+            key(KeyCode::Char('r')),
+            key(KeyCode::Char('q')),
+        ],
+    );
+    let on_disk: Config = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(on_disk.retries, 2);
+    assert_eq!(saved.retries, 2);
+    assert_eq!(saved.name, "edge");
+}
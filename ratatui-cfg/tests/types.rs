@@ -0,0 +1,221 @@
+use {
+    ratatui_cfg::{ConfigMenu, FieldType, MenuController},
+    serde::{Deserialize, Serialize},
+    std::{
+        net::{IpAddr, SocketAddr},
+        num::NonZeroU32,
+        path::PathBuf,
+        time::Duration,
+    },
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Csv {
+    separator: char,
+}
+
+#[test]
+fn char_fields_take_exactly_one_character() {
+    let mut controller = MenuController::new(Csv { separator: ',' });
+    assert_eq!(
+        controller.menu_state.current_items()[0].field_type,
+        FieldType::Char
+    );
+
+    controller.set_field(&["separator"], ";").unwrap();
+    assert_eq!(controller.config.separator, ';');
+    assert!(controller.set_field(&["separator"], ";;").is_err());
+    assert_eq!(controller.get_field(&["separator"]).as_deref(), Some(";"));
+
+    let saved = toml::to_string(&controller.config).unwrap();
+    assert_eq!(toml::from_str::<Csv>(&saved).unwrap().separator, ';');
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigMenu)]
+struct Pool {
+    workers: NonZeroU32,
+}
+
+#[test]
+fn non_zero_fields_reject_zero() {
+    let mut controller = MenuController::new(Pool {
+        workers: NonZeroU32::new(4).unwrap(),
+    });
+
+    assert!(controller.set_field(&["workers"], "0").is_err());
+    assert_eq!(controller.config.workers.get(), 4);
+    assert_eq!(controller.menu_state.current_items()[0].value, "4");
+
+    controller.set_field(&["workers"], "8").unwrap();
+    assert_eq!(controller.config.workers.get(), 8);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Storage {
+    data_dir: PathBuf,
+}
+
+#[test]
+fn path_fields_are_edited_as_plain_text() {
+    let mut controller = MenuController::new(Storage::default());
+    assert_eq!(
+        controller.menu_state.current_items()[0].field_type,
+        FieldType::Path
+    );
+
+    controller
+        .set_field(&["data_dir"], "/var/lib/app data")
+        .unwrap();
+    assert_eq!(
+        controller.config.data_dir,
+        PathBuf::from("/var/lib/app data")
+    );
+    assert_eq!(
+        controller.menu_state.current_items()[0].value,
+        "/var/lib/app data"
+    );
+
+    controller.start_editing();
+    assert_eq!(controller.edit_buffer, "/var/lib/app data");
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigMenu)]
+struct Listen {
+    bind: SocketAddr,
+    peer: IpAddr,
+}
+
+#[test]
+fn addresses_parse_with_from_str() {
+    let mut controller = MenuController::new(Listen {
+        bind: "0.0.0.0:80".parse().unwrap(),
+        peer: "::1".parse().unwrap(),
+    });
+
+    controller.set_field(&["bind"], "127.0.0.1:8080").unwrap();
+    assert_eq!(controller.config.bind, "127.0.0.1:8080".parse().unwrap());
+    assert_eq!(
+        controller.menu_state.current_items()[0].value,
+        "127.0.0.1:8080"
+    );
+
+    let error = controller.set_field(&["bind"], "not:an:addr").unwrap_err();
+    assert!(error.contains("invalid socket address"));
+    controller.set_field(&["peer"], "10.0.0.1").unwrap();
+    assert_eq!(
+        controller.config.peer,
+        "10.0.0.1".parse::<IpAddr>().unwrap()
+    );
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Timeouts {
+    idle: Duration,
+}
+
+#[test]
+fn durations_parse_humanized_input() {
+    let mut controller = MenuController::new(Timeouts::default());
+
+    for input in ["90s", "1m30s", "90"] {
+        controller.set_field(&["idle"], input).unwrap();
+        assert_eq!(controller.config.idle, Duration::from_secs(90));
+    }
+    assert_eq!(controller.menu_state.current_items()[0].value, "1m30s");
+
+    controller.set_field(&["idle"], "1500ms").unwrap();
+    assert_eq!(controller.config.idle, Duration::from_millis(1500));
+    assert!(controller.set_field(&["idle"], "soon").is_err());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Theme {
+    color: (u8, u8, u8),
+}
+
+#[test]
+fn tuple_elements_are_edited_by_position() {
+    let mut controller = MenuController::new(Theme {
+        color: (10, 20, 30),
+    });
+    controller.enter_submenu().unwrap();
+
+    let labels: Vec<_> = controller
+        .menu_state
+        .current_items()
+        .iter()
+        .map(|item| item.label.clone())
+        .collect();
+    assert_eq!(labels, ["0", "1", "2"]);
+
+    controller.menu_state.select(1);
+    controller.start_editing();
+    controller.clear_line();
+    for c in "200".chars() {
+        controller.handle_edit_input(c);
+    }
+    controller.finish_editing().unwrap();
+    assert_eq!(controller.config.color, (10, 200, 30));
+    assert!(controller.set_field(&["color", "1"], "300").is_err());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Grid {
+    cells: [i32; 4],
+}
+
+#[test]
+fn array_elements_are_edited_but_not_resized() {
+    let mut controller = MenuController::new(Grid::default());
+    controller.enter_submenu().unwrap();
+    assert!(!controller.can_resize_elements());
+
+    controller.set_field(&["cells", "[2]"], "-7").unwrap();
+    assert_eq!(controller.config.cells, [0, 0, -7, 0]);
+
+    assert!(controller.push_vec_element().is_err());
+    assert!(controller.remove_vec_element().is_err());
+    assert_eq!(controller.config.cells.len(), 4);
+    assert_eq!(controller.menu_state.current_items().len(), 4);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Template {
+    body: String,
+}
+
+#[test]
+fn escaped_strings_survive_an_edit_cycle() {
+    for text in [
+        "line one\nline two",
+        "col\tcol",
+        "say \"hi\" \\ bye",
+        "emoji 🎉 \u{1b}",
+    ] {
+        let mut controller = MenuController::new(Template {
+            body: text.to_string(),
+        });
+
+        controller.start_editing();
+        assert_eq!(controller.edit_buffer, text);
+        controller.finish_editing().unwrap();
+        assert_eq!(controller.config.body, text);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Byte {
+    value: u8,
+}
+
+#[test]
+fn overflow_is_reported_apart_from_bad_syntax() {
+    let mut controller = MenuController::new(Byte::default());
+
+    let overflow = controller.set_field(&["value"], "300").unwrap_err();
+    let syntax = controller.set_field(&["value"], "3x").unwrap_err();
+
+    assert_eq!(overflow, "Value 300 is out of range for u8 (0..=255)");
+    assert_ne!(overflow, syntax);
+    assert_eq!(controller.status_message.as_deref(), Some(syntax.as_str()));
+}
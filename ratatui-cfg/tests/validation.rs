@@ -0,0 +1,120 @@
+use {
+    ratatui_cfg::{ConfigMenu, MenuController},
+    serde::{Deserialize, Serialize},
+};
+
+fn at_least_one(value: &u32) -> Result<(), String> {
+    if *value == 0 {
+        return Err("retries must be at least 1".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Limits {
+    #[config_menu(min = 1, max = 64)]
+    workers: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Config {
+    name: String,
+    #[config_menu(validate = "at_least_one")]
+    retries: u32,
+    limits: Limits,
+}
+
+#[test]
+fn goto_next_invalid_cycles_through_invalid_fields() {
+    let mut controller = MenuController::new(Config::default());
+
+    let invalid: Vec<_> = controller
+        .validate_all()
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(invalid, [vec!["retries"], vec!["limits", "workers"]]);
+
+    assert!(controller.goto_next_invalid());
+    assert_eq!(controller.menu_state.get_current_field_path(), ["retries"]);
+    assert!(controller.goto_next_invalid());
+    assert_eq!(
+        controller.menu_state.get_current_field_path(),
+        ["limits", "workers"]
+    );
+    assert!(controller.goto_next_invalid());
+    assert_eq!(controller.menu_state.get_current_field_path(), ["retries"]);
+}
+
+#[test]
+fn goto_next_invalid_reports_a_valid_config() {
+    let mut controller = MenuController::new(Config {
+        name: "server".to_string(),
+        retries: 3,
+        limits: Limits { workers: 4 },
+    });
+
+    assert!(controller.validate_all().is_empty());
+    assert!(!controller.goto_next_invalid());
+}
+
+#[test]
+fn validate_file_reports_problems_without_loading() {
+    let path =
+        std::env::temp_dir().join(format!("ratatui-cfg-{}-invalid.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "name = \"disk\"\nretries = 2\n\n[limits]\nworkers = 500\n",
+    )
+    .unwrap();
+    let controller = MenuController::new(Config::default());
+
+    let problems = controller.validate_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].0, ["limits", "workers"]);
+    assert!(problems[0].1.contains("maximum"));
+    assert_eq!(controller.config.name, "");
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Volume {
+    #[config_menu(max = 10)]
+    level: u8,
+}
+
+#[test]
+fn out_of_range_edits_are_clamped() {
+    let mut controller = MenuController::new(Volume::default());
+
+    controller.set_field(&["level"], "20").unwrap();
+    assert_eq!(controller.config.level, 10);
+    assert!(controller.status_message.unwrap().contains("clamped to 10"));
+}
+
+fn at_most_100(value: &u32) -> Result<(), String> {
+    if *value > 100 {
+        return Err("volume must be at most 100".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ConfigMenu)]
+struct Mixer {
+    #[config_menu(validate = "at_most_100")]
+    volume: u32,
+}
+
+#[test]
+fn rejected_values_keep_the_old_one() {
+    let mut controller = MenuController::new(Mixer::default());
+    controller.set_field(&["volume"], "80").unwrap();
+
+    assert!(controller.set_field(&["volume"], "120").is_err());
+    assert_eq!(controller.config.volume, 80);
+    assert_eq!(
+        controller.status_message.as_deref(),
+        Some("volume must be at most 100")
+    );
+}